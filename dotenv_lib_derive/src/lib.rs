@@ -0,0 +1,247 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    Data, DeriveInput, Fields, GenericArgument, LitStr, PathArguments, Type, parse_macro_input,
+};
+
+/// Derives `from_env_file` and `from_parsed_env` constructors that read each field from a `.env` file,
+/// along with a `Debug` implementation that redacts secret fields.
+///
+/// Every field is read from its name in screaming snake case, as `database_url` and `databaseUrl` both
+/// read `DATABASE_URL`, unless `#[env(key = "...")]` is given. Fields may also set
+/// `#[env(default = "...")]` for a value to use when the key is missing, and `#[env(secret)]` to hide
+/// the value in `Debug` output. Defaults for `bool`, `char`, `String` and numeric fields are checked
+/// when the macro expands, and any other invalid default is reported when the config is read.
+/// `Option` fields are `None` when the key is missing, and `bool` fields accept 1/0, true/false,
+/// yes/no and on/off. Every other field type is parsed with `FromStr`.
+#[proc_macro_derive(EnvConfig, attributes(env))]
+pub fn derive_env_config(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+struct EnvField {
+    ident: syn::Ident,
+    /// the key given with `#[env(key = "...")]`
+    key: Option<String>,
+    default: Option<LitStr>,
+    secret: bool,
+    ty: Type,
+}
+
+fn parse_field(field: &syn::Field) -> syn::Result<EnvField> {
+    let ident = field
+        .ident
+        .clone()
+        .ok_or_else(|| syn::Error::new_spanned(field, "EnvConfig fields must be named"))?;
+    let mut env_field = EnvField {
+        key: None,
+        ident,
+        default: None,
+        secret: false,
+        ty: field.ty.clone(),
+    };
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("env")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("key") {
+                env_field.key = Some(meta.value()?.parse::<LitStr>()?.value());
+                Ok(())
+            } else if meta.path.is_ident("default") {
+                env_field.default = Some(meta.value()?.parse::<LitStr>()?);
+                Ok(())
+            } else if meta.path.is_ident("secret") {
+                env_field.secret = true;
+                Ok(())
+            } else {
+                Err(meta.error("expected `key`, `default` or `secret`"))
+            }
+        })?;
+    }
+    if let Some(default) = &env_field.default {
+        let ty = option_inner(&env_field.ty).unwrap_or(&env_field.ty);
+        if let Err(reason) = check_default(ty, &default.value()) {
+            return Err(syn::Error::new_spanned(
+                default,
+                format!("invalid default for this field: {reason}"),
+            ));
+        }
+    }
+    Ok(env_field)
+}
+
+/// checks a default for the field types whose parsing is known when the macro expands, which
+/// are parsed the same way at runtime
+fn check_default(ty: &Type, default: &str) -> Result<(), String> {
+    fn parses<T: std::str::FromStr>(value: &str) -> Result<(), String>
+    where
+        T::Err: std::fmt::Display,
+    {
+        value
+            .parse::<T>()
+            .map(|_| ())
+            .map_err(|err| err.to_string())
+    }
+    let Type::Path(path) = ty else { return Ok(()) };
+    let Some(ident) = path.path.get_ident() else {
+        return Ok(());
+    };
+    match ident.to_string().as_str() {
+        // the same words as `dotenv_lib`'s own boolean parsing
+        "bool" => {
+            const WORDS: [&str; 8] = ["1", "0", "true", "false", "yes", "no", "on", "off"];
+            if WORDS.iter().any(|w| w.eq_ignore_ascii_case(default.trim())) {
+                Ok(())
+            } else {
+                Err("expected one of 1/0, true/false, yes/no, on/off".to_string())
+            }
+        }
+        "char" => parses::<char>(default),
+        "i8" => parses::<i8>(default),
+        "i16" => parses::<i16>(default),
+        "i32" => parses::<i32>(default),
+        "i64" => parses::<i64>(default),
+        "i128" => parses::<i128>(default),
+        "isize" => parses::<isize>(default),
+        "u8" => parses::<u8>(default),
+        "u16" => parses::<u16>(default),
+        "u32" => parses::<u32>(default),
+        "u64" => parses::<u64>(default),
+        "u128" => parses::<u128>(default),
+        "usize" => parses::<usize>(default),
+        "f32" => parses::<f32>(default),
+        "f64" => parses::<f64>(default),
+        _ => Ok(()),
+    }
+}
+
+/// returns `T` when `ty` is written as `Option<T>`
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else { return None };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        GenericArgument::Type(inner) => Some(inner),
+        _ => None,
+    }
+}
+
+fn parser_for(ty: &Type) -> TokenStream2 {
+    match ty {
+        Type::Path(path) if path.path.is_ident("bool") => {
+            quote!(::dotenv_lib::__private::parse_bool)
+        }
+        _ => quote!(::dotenv_lib::__private::parse_from_str::<#ty>),
+    }
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input,
+            "EnvConfig can only be derived for structs",
+        ));
+    };
+    let Fields::Named(named) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            &input,
+            "EnvConfig can only be derived for structs with named fields",
+        ));
+    };
+    let fields = named
+        .named
+        .iter()
+        .map(parse_field)
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let reads = fields.iter().map(|field| {
+        let ident = &field.ident;
+        let key = match &field.key {
+            Some(key) => quote!(#key),
+            None => {
+                let name = ident.to_string();
+                let name = name.trim_start_matches("r#");
+                quote!(&::dotenv_lib::__private::field_key(#name))
+            }
+        };
+        let default = match &field.default {
+            Some(default) => quote!(::core::option::Option::Some(#default)),
+            None => quote!(::core::option::Option::None),
+        };
+        match option_inner(&field.ty) {
+            Some(inner) => {
+                let parser = parser_for(inner);
+                quote! {
+                    let #ident = ::dotenv_lib::__private::read_optional(env, #key, #default, #parser, &mut errors);
+                }
+            }
+            None => {
+                let parser = parser_for(&field.ty);
+                quote! {
+                    let #ident = ::dotenv_lib::__private::read_required(env, #key, #default, #parser, &mut errors);
+                }
+            }
+        }
+    });
+    let assigns = fields.iter().map(|field| {
+        let ident = &field.ident;
+        match option_inner(&field.ty) {
+            Some(_) => quote!(#ident),
+            None => quote!(#ident: #ident.unwrap()),
+        }
+    });
+    let debug_fields = fields.iter().map(|field| {
+        let ident = &field.ident;
+        let label = ident.to_string();
+        if field.secret {
+            quote!(.field(#label, &::dotenv_lib::__private::Redacted))
+        } else {
+            quote!(.field(#label, &self.#ident))
+        }
+    });
+    let label = name.to_string();
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Reads and parses the `.env` file at `path`, then builds the config from it.
+            pub fn from_env_file(
+                path: impl ::core::convert::AsRef<::std::path::Path>,
+            ) -> ::core::result::Result<Self, ::dotenv_lib::ConfigError> {
+                let contents = ::std::fs::read_to_string(path)?;
+                let env = ::dotenv_lib::parse_env(contents)?;
+                Self::from_parsed_env(&env)
+            }
+
+            /// Builds the config from a parsed `.env` file, reporting every missing or invalid key at once.
+            pub fn from_parsed_env(
+                env: &::dotenv_lib::ParsedEnv,
+            ) -> ::core::result::Result<Self, ::dotenv_lib::ConfigError> {
+                let mut errors = ::std::vec::Vec::new();
+                #(#reads)*
+                if !errors.is_empty() {
+                    return ::core::result::Result::Err(::dotenv_lib::ConfigError::Invalid(errors));
+                }
+                ::core::result::Result::Ok(Self {
+                    #(#assigns,)*
+                })
+            }
+        }
+
+        impl #impl_generics ::core::fmt::Debug for #name #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                f.debug_struct(#label)
+                    #(#debug_fields)*
+                    .finish()
+            }
+        }
+    })
+}
@@ -0,0 +1,127 @@
+// structured `# @name argument` comments that describe the entry below them
+
+/// The annotation comments written directly above an entry, such as `# @type port`,
+/// `# @required`, `# @secret`, `# @default 8080` or `# @description HTTP port`.
+///
+/// Comment lines that do not start with `@` are ignored, and a blank line discards any annotations
+/// collected so far.
+/// ```rust
+/// # use dotenv_lib::parse_env;
+/// let env = parse_env("# @type port\n# @default 8080\nPORT=3000\n".to_string()).unwrap();
+/// let annotations = &env.entry("PORT").unwrap().annotations;
+/// assert_eq!(annotations.value_type(), Some("port"));
+/// assert_eq!(annotations.default_value(), Some("8080"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Annotations {
+    tags: Vec<(String, String)>,
+}
+
+impl Annotations {
+    /// reads `@name argument` from the text of a comment line, ignoring anything else
+    pub(crate) fn add_comment(&mut self, comment: &str) {
+        let Some(tag) = comment.trim().strip_prefix('@') else {
+            return;
+        };
+        let (name, argument) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+        if !name.is_empty() {
+            self.tags
+                .push((name.to_string(), argument.trim().to_string()));
+        }
+    }
+
+    /// Returns the argument of the last `@name` annotation, or an empty string for a bare flag.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .rev()
+            .find(|(n, _)| n == name)
+            .map(|(_, argument)| argument.as_str())
+    }
+
+    pub fn has(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Returns every annotation as a name and argument, in the order they were written.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.tags.iter().map(|(n, a)| (n.as_str(), a.as_str()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+    }
+
+    /// The argument of `@type`, such as `port` or `enum(debug|info)`.
+    pub fn value_type(&self) -> Option<&str> {
+        self.get("type")
+    }
+
+    pub fn is_required(&self) -> bool {
+        self.has("required")
+    }
+
+    pub fn is_secret(&self) -> bool {
+        self.has("secret")
+    }
+
+    pub fn default_value(&self) -> Option<&str> {
+        self.get("default")
+    }
+
+    /// Joins every `@description` line with a space, so long descriptions can span several comments.
+    pub fn description(&self) -> Option<String> {
+        let lines: Vec<&str> = self
+            .iter()
+            .filter(|(name, _)| *name == "description")
+            .map(|(_, argument)| argument)
+            .collect();
+        if lines.is_empty() {
+            None
+        } else {
+            Some(lines.join(" "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse_env;
+
+    #[test]
+    fn annotations_attach_to_next_entry() {
+        let contents = "# @type port\n# @required\n# plain comment\n# @description the app's HTTP port\n# @description in production\nPORT=8080\nOTHER=1\n";
+        let env = parse_env(contents.to_string()).expect("error processing env file");
+        let port = &env.entry("PORT").unwrap().annotations;
+        assert_eq!(port.value_type(), Some("port"));
+        assert!(port.is_required());
+        assert!(!port.is_secret());
+        assert_eq!(
+            port.description().unwrap(),
+            "the app's HTTP port in production"
+        );
+        assert!(env.entry("OTHER").unwrap().annotations.is_empty());
+    }
+
+    /// a blank line between the annotations and the entry detaches them
+    #[test]
+    fn blank_line_discards_annotations() {
+        let env = parse_env("# @secret\n\nTOKEN=abc\n".to_string()).unwrap();
+        assert!(env.entry("TOKEN").unwrap().annotations.is_empty());
+    }
+
+    /// comments after a value on the same line are not annotations for the next entry
+    #[test]
+    fn trailing_comment_is_not_an_annotation() {
+        let env = parse_env("A=1 # @secret\nB=2\n".to_string()).unwrap();
+        assert!(env.entry("B").unwrap().annotations.is_empty());
+    }
+
+    /// quotes and assignment operators inside comments are not parsed
+    #[test]
+    fn special_characters_in_comments() {
+        let env = parse_env("# don't use \"a=b\" here\nA=1 # it's = fine\n".to_string())
+            .expect("error processing env file");
+        assert_eq!(env.get_str("A"), Some("1"));
+    }
+}
@@ -0,0 +1,69 @@
+// standard, padded base64, used for generated values and Kubernetes secrets
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// encodes bytes as standard, padded base64
+pub(crate) fn encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |group, (i, b)| group | (*b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(group >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// decodes standard, padded base64, returning `None` if `encoded` is not valid
+// only reading Kubernetes secrets needs this, which takes the `yaml` feature
+#[cfg_attr(not(feature = "yaml"), allow(dead_code))]
+pub(crate) fn decode(encoded: &str) -> Option<Vec<u8>> {
+    let encoded = encoded.as_bytes();
+    if !encoded.len().is_multiple_of(4) {
+        return None;
+    }
+    let mut bytes = Vec::with_capacity(encoded.len() / 4 * 3);
+    let groups = encoded.len() / 4;
+    for (n, chunk) in encoded.chunks(4).enumerate() {
+        // padding is only allowed at the end of the last group
+        let padding = chunk.iter().rev().take_while(|&&c| c == b'=').count();
+        if padding > 2 || (padding > 0 && n + 1 != groups) {
+            return None;
+        }
+        let mut group = 0u32;
+        for (i, c) in chunk[..4 - padding].iter().enumerate() {
+            let sextet = ALPHABET.iter().position(|a| a == c)? as u32;
+            group |= sextet << (18 - 6 * i);
+        }
+        bytes.extend(group.to_be_bytes()[1..4 - padding].iter());
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, encode};
+
+    #[test]
+    fn encode_and_decode() {
+        for (bytes, encoded) in [
+            (&b""[..], ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foo", "Zm9v"),
+            (b"hello world", "aGVsbG8gd29ybGQ="),
+        ] {
+            assert_eq!(encode(bytes), encoded);
+            assert_eq!(decode(encoded).as_deref(), Some(bytes));
+        }
+        for invalid in ["Zg=", "Z===", "Zg==Zm9v", "Zm9v!A==", "Zm 9"] {
+            assert_eq!(decode(invalid), None, "{invalid}");
+        }
+    }
+}
@@ -0,0 +1,131 @@
+// a small command-line argument parser, so the binary needs no dependencies
+
+/// The options, flags and positional arguments given to a subcommand.
+#[derive(Debug, Default, PartialEq)]
+pub struct Args {
+    options: Vec<(String, String)>,
+    flags: Vec<String>,
+    pub positional: Vec<String>,
+    /// everything after a `--` separator
+    pub rest: Vec<String>,
+}
+
+/// A named option of a subcommand, such as `--env PATH` (`takes_value`) or `--check`.
+/// `names` lists the long name first, followed by any aliases such as `-f`.
+pub struct Opt {
+    pub names: &'static [&'static str],
+    pub takes_value: bool,
+}
+
+impl Args {
+    /// Parses `argv` against the options a subcommand accepts. Options are stored under their long name.
+    pub fn parse(argv: impl IntoIterator<Item = String>, opts: &[Opt]) -> Result<Args, String> {
+        let mut args = Args::default();
+        let mut argv = argv.into_iter();
+        while let Some(arg) = argv.next() {
+            if arg == "--" {
+                args.rest.extend(argv.by_ref());
+                break;
+            }
+            if !arg.starts_with('-') || arg == "-" {
+                args.positional.push(arg);
+                continue;
+            }
+            let (name, inline_value) = match arg.split_once('=') {
+                Some((name, value)) if arg.starts_with("--") => (name, Some(value.to_string())),
+                _ => (arg.as_str(), None),
+            };
+            let opt = opts
+                .iter()
+                .find(|o| o.names.contains(&name))
+                .ok_or_else(|| format!("unknown option '{name}'"))?;
+            let long_name = opt.names[0].to_string();
+            if opt.takes_value {
+                let value = match inline_value {
+                    Some(value) => value,
+                    None => argv
+                        .next()
+                        .ok_or_else(|| format!("option '{name}' needs a value"))?,
+                };
+                args.options.push((long_name, value));
+            } else if inline_value.is_some() {
+                return Err(format!("option '{name}' does not take a value"));
+            } else {
+                args.flags.push(long_name);
+            }
+        }
+        Ok(args)
+    }
+
+    /// Returns the last value given for an option.
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .rev()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Returns every value given for an option that may be repeated, in order.
+    pub fn values(&self, name: &str) -> Vec<&str> {
+        self.options
+            .iter()
+            .filter(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+            .collect()
+    }
+
+    pub fn flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|f| f == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Args, Opt};
+
+    const OPTS: &[Opt] = &[
+        Opt {
+            names: &["--file", "-f"],
+            takes_value: true,
+        },
+        Opt {
+            names: &["--check"],
+            takes_value: false,
+        },
+    ];
+
+    fn argv(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_options_flags_and_rest() {
+        let args = Args::parse(
+            argv(&[
+                "-f",
+                "a.env",
+                "KEY",
+                "--file=b.env",
+                "--check",
+                "--",
+                "cmd",
+                "--x",
+            ]),
+            OPTS,
+        )
+        .unwrap();
+        assert_eq!(args.value("--file"), Some("b.env"));
+        assert_eq!(args.values("--file"), vec!["a.env", "b.env"]);
+        assert!(args.flag("--check"));
+        assert_eq!(args.positional, vec!["KEY"]);
+        assert_eq!(args.rest, vec!["cmd", "--x"]);
+    }
+
+    #[test]
+    fn reject_unknown_and_incomplete_options() {
+        assert!(Args::parse(argv(&["--nope"]), OPTS).is_err());
+        assert!(Args::parse(argv(&["--file"]), OPTS).is_err());
+        assert!(Args::parse(argv(&["--check=yes"]), OPTS).is_err());
+    }
+}
@@ -0,0 +1,98 @@
+// `dotenv check`: compare a `.env` file with its `.env.example`
+use std::path::Path;
+use std::process::ExitCode;
+
+use dotenv_lib::{check_against_example, compare_with_sh};
+
+use crate::args::{Args, Opt};
+use crate::load_env;
+
+pub const USAGE: &str = "dotenv check [--env PATH] [--example PATH] [--allow-extra]
+       dotenv check --sh [--env PATH]
+    Reports keys missing from the env file, keys not in the example, and values that
+    still look like placeholders. Exits with 1 when any are found.
+    With --sh, sources the env file in /bin/sh instead and reports every key the shell
+    sets to a different value than this crate reads. This runs the file as a script, so
+    only use it on files you trust.
+    --env PATH        env file to check (default: .env)
+    --example PATH    example file to check against (default: .env.example)
+    --allow-extra     report keys that are not in the example without failing
+    --sh              compare with the values /bin/sh sets when sourcing the file";
+
+const OPTS: &[Opt] = &[
+    Opt {
+        names: &["--env"],
+        takes_value: true,
+    },
+    Opt {
+        names: &["--example"],
+        takes_value: true,
+    },
+    Opt {
+        names: &["--allow-extra"],
+        takes_value: false,
+    },
+    Opt {
+        names: &["--sh"],
+        takes_value: false,
+    },
+];
+
+pub fn run(argv: Vec<String>) -> Result<ExitCode, String> {
+    let args = Args::parse(argv, OPTS)?;
+    if let Some(arg) = args.positional.iter().chain(&args.rest).next() {
+        return Err(format!(
+            "unexpected argument '{arg}', use --env PATH\n  {USAGE}"
+        ));
+    }
+    let path = args.value("--env").unwrap_or(".env");
+    if args.flag("--sh") {
+        // the shell comparison does not read an example, so these would be silently ignored
+        if args.value("--example").is_some() || args.flag("--allow-extra") {
+            return Err(format!(
+                "--example and --allow-extra cannot be used with --sh\n  {USAGE}"
+            ));
+        }
+        let mismatches = compare_with_sh(Path::new(path))
+            .map_err(|err| format!("cannot check {path}: {err}"))?;
+        for mismatch in &mismatches {
+            println!("{path}:{mismatch}");
+        }
+        return Ok(if mismatches.is_empty() {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        });
+    }
+    let env = load_env(path)?;
+    let example = load_env(args.value("--example").unwrap_or(".env.example"))?;
+    let report = check_against_example(&env, &example);
+    print!("{report}");
+    let failed = !report.missing.is_empty()
+        || !report.placeholders.is_empty()
+        || (!report.extra.is_empty() && !args.flag("--allow-extra"));
+    Ok(if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::run;
+
+    #[test]
+    fn sh_rejects_ignored_arguments() {
+        let argv = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect();
+        for args in [
+            &["--sh", "--example", ".env.example"][..],
+            &["--sh", "--allow-extra"],
+            &["--sh", ".env.local"],
+            &["--sh", "--", ".env.local"],
+        ] {
+            let err = run(argv(args)).unwrap_err();
+            assert!(err.contains("dotenv check --sh"), "{args:?}: {err}");
+        }
+    }
+}
@@ -0,0 +1,142 @@
+// `dotenv convert`: convert between env files and flat JSON, YAML and TOML objects
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::process::ExitCode;
+
+use dotenv_lib::{
+    ArrayStyle, Dialect, EnvMap, env_map_to_env_string, parse_docker_env_file,
+    parse_systemd_env_file, process_dot_env, systemd_env_file,
+};
+
+use crate::args::{Args, Opt};
+
+pub const USAGE: &str =
+    "dotenv convert --to FORMAT [--from FORMAT] [--arrays joined|indexed] [PATH]
+    Converts PATH, or standard input, between env, systemd, json, yaml and toml, printing the
+    result, where systemd is the format of a unit's EnvironmentFile=. Nested objects become
+    A__B keys. JSON, YAML and TOML need the crate features of the same name. --from docker
+    reads a file as `docker run --env-file` would, with quotes kept in values and bare keys
+    taken from the environment.
+    --to FORMAT       format to write
+    --from FORMAT     format or dialect to read (default: from the file extension,
+                      otherwise env)
+    --arrays STYLE    joined (default) writes arrays as comma separated values, indexed as
+                      A__0, A__1 keys";
+
+const OPTS: &[Opt] = &[
+    Opt {
+        names: &["--to"],
+        takes_value: true,
+    },
+    Opt {
+        names: &["--from"],
+        takes_value: true,
+    },
+    Opt {
+        names: &["--arrays"],
+        takes_value: true,
+    },
+];
+
+#[cfg(not(all(feature = "json", feature = "yaml", feature = "toml")))]
+fn without_feature(format: &str) -> String {
+    format!("dotenv was built without the `{format}` feature")
+}
+
+fn read(contents: String, format: &str, arrays: ArrayStyle) -> Result<EnvMap, String> {
+    #[cfg(not(any(feature = "json", feature = "yaml", feature = "toml")))]
+    let _ = arrays;
+    if let Ok(dialect) = format.parse::<Dialect>() {
+        return dialect
+            .parse(&contents, |key| env::var(key).ok())
+            .map(EnvMap::from)
+            .map_err(|err| err.to_string());
+    }
+    match format {
+        "env" => process_dot_env(contents).map_err(|err| err.to_string()),
+        "docker" => parse_docker_env_file(&contents, |key| env::var(key).ok())
+            .map(EnvMap::from)
+            .map_err(|err| err.to_string()),
+        "systemd" => Ok(parse_systemd_env_file(&contents).into()),
+        #[cfg(feature = "json")]
+        "json" => dotenv_lib::json_to_env_map(&contents, arrays).map_err(|err| err.to_string()),
+        #[cfg(feature = "yaml")]
+        "yaml" => dotenv_lib::yaml_to_env_map(&contents, arrays).map_err(|err| err.to_string()),
+        #[cfg(feature = "toml")]
+        "toml" => dotenv_lib::toml_to_env_map(&contents, arrays).map_err(|err| err.to_string()),
+        #[cfg(not(feature = "json"))]
+        "json" => Err(without_feature(format)),
+        #[cfg(not(feature = "yaml"))]
+        "yaml" => Err(without_feature(format)),
+        #[cfg(not(feature = "toml"))]
+        "toml" => Err(without_feature(format)),
+        other => Err(format!(
+            "unknown format '{other}', expected env, docker, systemd, a dialect, json, yaml or toml"
+        )),
+    }
+}
+
+fn write(env: &EnvMap, format: &str) -> Result<String, String> {
+    match format {
+        "env" => env_map_to_env_string(env).map_err(|err| err.to_string()),
+        "systemd" => {
+            let mut vars: Vec<(&str, &str)> =
+                env.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+            vars.sort();
+            systemd_env_file(vars).map_err(|err| err.to_string())
+        }
+        #[cfg(feature = "json")]
+        "json" => dotenv_lib::env_map_to_json(env)
+            .map(|json| json + "\n")
+            .map_err(|err| err.to_string()),
+        #[cfg(feature = "yaml")]
+        "yaml" => dotenv_lib::env_map_to_yaml(env).map_err(|err| err.to_string()),
+        #[cfg(feature = "toml")]
+        "toml" => dotenv_lib::env_map_to_toml(env).map_err(|err| err.to_string()),
+        #[cfg(not(feature = "json"))]
+        "json" => Err(without_feature(format)),
+        #[cfg(not(feature = "yaml"))]
+        "yaml" => Err(without_feature(format)),
+        #[cfg(not(feature = "toml"))]
+        "toml" => Err(without_feature(format)),
+        other => Err(format!(
+            "unknown format '{other}', expected env, systemd, json, yaml or toml"
+        )),
+    }
+}
+
+pub fn run(argv: Vec<String>) -> Result<ExitCode, String> {
+    let args = Args::parse(argv, OPTS)?;
+    let to = args
+        .value("--to")
+        .ok_or_else(|| format!("--to is required\n  {USAGE}"))?;
+    let arrays = match args.value("--arrays").unwrap_or("joined") {
+        "joined" => ArrayStyle::Joined,
+        "indexed" => ArrayStyle::Indexed,
+        other => return Err(format!("unknown array style '{other}'")),
+    };
+    let path = args.positional.first().map(String::as_str);
+    let from = match (args.value("--from"), path) {
+        (Some(from), _) => from,
+        (None, Some(path)) if path.ends_with(".json") => "json",
+        (None, Some(path)) if path.ends_with(".yaml") || path.ends_with(".yml") => "yaml",
+        (None, Some(path)) if path.ends_with(".toml") => "toml",
+        _ => "env",
+    };
+    let contents = match path {
+        Some(path) if path != "-" => {
+            fs::read_to_string(path).map_err(|err| format!("cannot read {path}: {err}"))?
+        }
+        _ => {
+            let mut contents = String::new();
+            io::stdin()
+                .read_to_string(&mut contents)
+                .map_err(|err| format!("cannot read standard input: {err}"))?;
+            contents
+        }
+    };
+    let env = read(contents, from, arrays)?;
+    print!("{}", write(&env, to)?);
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,55 @@
+// `dotenv docker`: print an env file in a form Docker reads without reinterpreting the values
+use std::process::ExitCode;
+
+use dotenv_lib::{compose_environment, docker_env_file, docker_run_args, dockerfile_env};
+
+use crate::args::{Args, Opt};
+use crate::load_env;
+
+pub const USAGE: &str = "dotenv docker [--env PATH] [--format run|env-file|dockerfile|compose]
+    Prints the variables in the env file for Docker, quoted so Docker reads back the values
+    this crate parses. Docker's --env-file keeps quotes as part of the value, so files
+    written for this crate can differ when passed to it directly.
+    --env PATH        env file to read (default: .env)
+    --format FORMAT   run (default) prints -e arguments quoted for a POSIX shell,
+                      env-file a file for --env-file, dockerfile an ENV instruction and
+                      compose the environment: block of a compose service";
+
+const OPTS: &[Opt] = &[
+    Opt {
+        names: &["--env"],
+        takes_value: true,
+    },
+    Opt {
+        names: &["--format"],
+        takes_value: true,
+    },
+];
+
+pub fn run(argv: Vec<String>) -> Result<ExitCode, String> {
+    let args = Args::parse(argv, OPTS)?;
+    let env = load_env(args.value("--env").unwrap_or(".env"))?;
+    let vars = env
+        .entries()
+        .iter()
+        .map(|e| (e.key.as_str(), e.value.as_str()));
+    let output = match args.value("--format").unwrap_or("run") {
+        "run" => docker_run_args(vars).map(|args| {
+            let quoted: Vec<String> = args
+                .iter()
+                .map(|arg| format!("'{}'", arg.replace('\'', r"'\''")))
+                .collect();
+            quoted.join(" ") + "\n"
+        }),
+        "env-file" => docker_env_file(vars),
+        "dockerfile" => dockerfile_env(vars),
+        "compose" => compose_environment(vars),
+        other => {
+            return Err(format!(
+                "unknown format '{other}', expected run, env-file, dockerfile or compose"
+            ));
+        }
+    };
+    print!("{}", output.map_err(|err| err.to_string())?);
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,42 @@
+// `dotenv example`: write a `.env.example` from a real `.env` file
+use std::process::ExitCode;
+
+use crate::args::{Args, Opt};
+use crate::load_document;
+
+pub const USAGE: &str = "dotenv example [--env PATH] [--output PATH] [--placeholder TEXT]
+    Prints the env file with its values masked, keeping comments and layout. Values
+    annotated @safe are kept and values with a @default annotation take the default.
+    --env PATH           env file to read (default: .env)
+    --output PATH, -o    write to a file instead of standard output
+    --placeholder TEXT   value that replaces masked values (default: changeme)";
+
+const OPTS: &[Opt] = &[
+    Opt {
+        names: &["--env"],
+        takes_value: true,
+    },
+    Opt {
+        names: &["--output", "-o"],
+        takes_value: true,
+    },
+    Opt {
+        names: &["--placeholder"],
+        takes_value: true,
+    },
+];
+
+pub fn run(argv: Vec<String>) -> Result<ExitCode, String> {
+    let args = Args::parse(argv, OPTS)?;
+    let document = load_document(args.value("--env").unwrap_or(".env"))?;
+    let example = document
+        .to_example(args.value("--placeholder").unwrap_or("changeme"))
+        .map_err(|err| format!("cannot write placeholder: {err}"))?;
+    match args.value("--output") {
+        Some(path) => example
+            .save(path)
+            .map_err(|err| format!("cannot write {path}: {err}"))?,
+        None => print!("{example}"),
+    }
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,36 @@
+// `dotenv export`: print an env file as shell statements, for `eval "$(dotenv export)"`
+use std::process::ExitCode;
+
+use dotenv_lib::{Shell, shell_script};
+
+use crate::args::{Args, Opt};
+use crate::load_env;
+
+pub const USAGE: &str = "dotenv export [--env PATH] [--shell bash|zsh|fish|pwsh]
+    Prints a statement exporting each variable in the env file, quoted for the shell, so
+    `eval \"$(dotenv export)\"` loads the file into the current shell.
+    --env PATH      env file to read (default: .env)
+    --shell SHELL   bash (default), zsh, fish or pwsh";
+
+const OPTS: &[Opt] = &[
+    Opt {
+        names: &["--env"],
+        takes_value: true,
+    },
+    Opt {
+        names: &["--shell"],
+        takes_value: true,
+    },
+];
+
+pub fn run(argv: Vec<String>) -> Result<ExitCode, String> {
+    let args = Args::parse(argv, OPTS)?;
+    let shell: Shell = args.value("--shell").unwrap_or("bash").parse()?;
+    let env = load_env(args.value("--env").unwrap_or(".env"))?;
+    let vars = env
+        .entries()
+        .iter()
+        .map(|e| (e.key.as_str(), e.value.as_str()));
+    print!("{}", shell_script(vars, shell)?);
+    Ok(ExitCode::SUCCESS)
+}
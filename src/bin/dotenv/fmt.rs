@@ -0,0 +1,62 @@
+// `dotenv fmt`: rewrite env files in the canonical style
+use std::fs;
+use std::process::ExitCode;
+
+use dotenv_lib::{EnvDocument, KeyOrder};
+
+use crate::args::{Args, Opt};
+
+pub const USAGE: &str = "dotenv fmt [--check] [--sort] [PATH]...
+    Rewrites each env file (default: .env) with values quoted only when needed, one blank
+    line between sections and a final newline.
+    --check    report files that would change without writing them, exiting with 1 if any
+    --sort     sort keys within each section, keeping comments with their keys";
+
+const OPTS: &[Opt] = &[
+    Opt {
+        names: &["--check"],
+        takes_value: false,
+    },
+    Opt {
+        names: &["--sort"],
+        takes_value: false,
+    },
+];
+
+pub fn run(argv: Vec<String>) -> Result<ExitCode, String> {
+    let args = Args::parse(argv, OPTS)?;
+    let order = if args.flag("--sort") {
+        KeyOrder::Sorted
+    } else {
+        KeyOrder::Preserve
+    };
+    let paths = match args.positional.as_slice() {
+        [] => vec![".env".to_string()],
+        paths => paths.to_vec(),
+    };
+    let mut unformatted = false;
+    for path in &paths {
+        let contents =
+            fs::read_to_string(path).map_err(|err| format!("cannot read {path}: {err}"))?;
+        let formatted = EnvDocument::parse(&contents)
+            .map_err(|err| format!("cannot parse {path}: {err}"))?
+            .formatted(order);
+        if formatted.to_string() == contents {
+            continue;
+        }
+        if args.flag("--check") {
+            println!("would reformat {path}");
+            unformatted = true;
+        } else {
+            formatted
+                .save(path)
+                .map_err(|err| format!("cannot write {path}: {err}"))?;
+            println!("formatted {path}");
+        }
+    }
+    Ok(if unformatted {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
@@ -0,0 +1,32 @@
+// `dotenv get`: print the value of a key
+use std::process::ExitCode;
+
+use crate::args::{Args, Opt};
+use crate::load_env;
+
+pub const USAGE: &str = "dotenv get [--env PATH] KEY
+    Prints the value of KEY, or exits with 1 if it is not set.
+    --env PATH    env file to read (default: .env)";
+
+const OPTS: &[Opt] = &[Opt {
+    names: &["--env"],
+    takes_value: true,
+}];
+
+pub fn run(argv: Vec<String>) -> Result<ExitCode, String> {
+    let args = Args::parse(argv, OPTS)?;
+    let [key] = args.positional.as_slice() else {
+        return Err(format!("expected one key\n  {USAGE}"));
+    };
+    let path = args.value("--env").unwrap_or(".env");
+    match load_env(path)?.get_str(key) {
+        Some(value) => {
+            println!("{value}");
+            Ok(ExitCode::SUCCESS)
+        }
+        None => {
+            eprintln!("dotenv: {key} is not set in {path}");
+            Ok(ExitCode::FAILURE)
+        }
+    }
+}
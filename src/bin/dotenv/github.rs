@@ -0,0 +1,61 @@
+// `dotenv github`: load an env file into the later steps of a GitHub Actions job
+use std::env;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::process::ExitCode;
+
+use dotenv_lib::{github_env_file, github_masks};
+
+use crate::args::{Args, Opt};
+use crate::load_env;
+
+pub const USAGE: &str = "dotenv github [--env PATH] [--mask] [--output PATH]
+    Appends the variables in the env file to the file named by $GITHUB_ENV, so later steps
+    of the job see them. Multi-line values are written as heredocs with random delimiters.
+    Without $GITHUB_ENV or --output, prints them instead.
+    --env PATH      env file to read (default: .env)
+    --mask          first print ::add-mask:: commands for values annotated @secret or
+                    named like credentials, hiding them in the job log
+    --output PATH   file to append to (default: $GITHUB_ENV)";
+
+const OPTS: &[Opt] = &[
+    Opt {
+        names: &["--env"],
+        takes_value: true,
+    },
+    Opt {
+        names: &["--mask"],
+        takes_value: false,
+    },
+    Opt {
+        names: &["--output"],
+        takes_value: true,
+    },
+];
+
+pub fn run(argv: Vec<String>) -> Result<ExitCode, String> {
+    let args = Args::parse(argv, OPTS)?;
+    let env = load_env(args.value("--env").unwrap_or(".env"))?;
+    let file = github_env_file(
+        env.entries()
+            .iter()
+            .map(|e| (e.key.as_str(), e.value.as_str())),
+    )?;
+    if args.flag("--mask") {
+        print!("{}", github_masks(&env));
+    }
+    let output = args
+        .value("--output")
+        .map(str::to_string)
+        .or_else(|| env::var("GITHUB_ENV").ok().filter(|path| !path.is_empty()));
+    match output {
+        Some(path) => OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut output| output.write_all(file.as_bytes()))
+            .map_err(|err| format!("cannot write {path}: {err}"))?,
+        None => print!("{file}"),
+    }
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,255 @@
+// `dotenv init`: create or complete a `.env` file by prompting for the keys in `.env.example`
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::Path;
+use std::process::ExitCode;
+
+use dotenv_lib::{DocEntry, EnvDocument, KeySpec, generate_value, looks_like_placeholder};
+
+use crate::args::{Args, Opt};
+use crate::load_document;
+
+pub const USAGE: &str = "dotenv init [--env PATH] [--example PATH]
+    Prompts for each key in the example that the env file does not set, showing its
+    @description, then writes the env file. Input for @secret keys is hidden, keys
+    annotated @generate (such as `@generate hex:32`) get random values, and pressing
+    enter accepts the value shown in brackets.
+    --env PATH        env file to write, created readable only by you if missing
+                      (default: .env)
+    --example PATH    example file to read keys from (default: .env.example)";
+
+const OPTS: &[Opt] = &[
+    Opt {
+        names: &["--env"],
+        takes_value: true,
+    },
+    Opt {
+        names: &["--example"],
+        takes_value: true,
+    },
+];
+
+pub fn run(argv: Vec<String>) -> Result<ExitCode, String> {
+    let args = Args::parse(argv, OPTS)?;
+    let path = args.value("--env").unwrap_or(".env");
+    let example = load_document(args.value("--example").unwrap_or(".env.example"))?;
+    let mut env = if Path::new(path).exists() {
+        load_document(path)?
+    } else {
+        EnvDocument::default()
+    };
+    let added = env.sync_from_example(&example)?;
+    if added.is_empty() {
+        println!("{path} already has every key in the example");
+        return Ok(ExitCode::SUCCESS);
+    }
+    let stdin = io::stdin();
+    let hide_secrets = stdin.is_terminal();
+    let mut input = stdin.lock();
+    let mut output = io::stdout();
+    for key in &added {
+        if let Some(entry) = env.get_mut(key) {
+            ask(entry, &mut input, &mut output, hide_secrets)?;
+        }
+    }
+    env.save(path)
+        .map_err(|err| format!("cannot write {path}: {err}"))?;
+    println!("wrote {} keys to {path}", added.len());
+    Ok(ExitCode::SUCCESS)
+}
+
+/// fills in the value of an entry copied from the example, by generating it or prompting for it
+fn ask(
+    entry: &mut DocEntry,
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    hide_secrets: bool,
+) -> Result<(), String> {
+    let key = entry.key.clone();
+    if let Some(spec) = entry.annotations.get("generate") {
+        let value = generate_value(spec).map_err(|err| format!("cannot generate {key}: {err}"))?;
+        entry.set_value(value)?;
+        writeln!(output, "{key}: generated").map_err(|err| err.to_string())?;
+        return Ok(());
+    }
+    let spec = KeySpec::from_entry(&entry.to_entry()).map_err(|err| err.to_string())?;
+    // the example's value is offered as a default unless it is a secret or a placeholder
+    let suggestion = (spec.default.is_some() || !spec.secret)
+        .then(|| entry.value.clone())
+        .filter(|value| !looks_like_placeholder(value));
+    let hide = spec.secret && hide_secrets;
+    let write_err = |err: io::Error| err.to_string();
+    if let Some(description) = &spec.description {
+        writeln!(output, "# {description}").map_err(write_err)?;
+    }
+    loop {
+        match (&suggestion, spec.secret) {
+            (Some(_), true) => write!(output, "{key} [keep default]: "),
+            (Some(value), false) => write!(output, "{key} [{value}]: "),
+            (None, _) => write!(output, "{key}: "),
+        }
+        .and_then(|_| output.flush())
+        .map_err(write_err)?;
+        let mut line = String::new();
+        let hidden = if hide {
+            match HiddenInput::new() {
+                Ok(hidden) => Some(hidden),
+                Err(err) => {
+                    write!(output, "(cannot hide input, it will be shown: {err}) ")
+                        .and_then(|_| output.flush())
+                        .map_err(write_err)?;
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let read = input.read_line(&mut line);
+        if hidden.is_some() {
+            drop(hidden);
+            writeln!(output).map_err(write_err)?;
+        }
+        if read.map_err(|err| format!("cannot read input: {err}"))? == 0 {
+            return Err(format!("no value given for {key}"));
+        }
+        let answer = line.trim_end_matches(['\n', '\r']);
+        let value = match (answer.is_empty(), &suggestion) {
+            (false, _) => answer.to_string(),
+            (true, Some(value)) => value.clone(),
+            (true, None) => {
+                writeln!(output, "{key} needs a value").map_err(write_err)?;
+                continue;
+            }
+        };
+        match spec
+            .value_type
+            .check(&value)
+            .and_then(|_| entry.set_value(value))
+        {
+            Ok(()) => return Ok(()),
+            Err(reason) => writeln!(output, "invalid value: {reason}").map_err(write_err)?,
+        }
+    }
+}
+
+/// turns terminal echo off while it is alive, so secrets are not shown as they are typed. echo
+/// is turned back on when it is dropped, including on errors and panics, and when the program is
+/// interrupted or terminated while it is off
+struct HiddenInput {
+    #[cfg(unix)]
+    original: libc::termios,
+}
+
+/// the terminal settings to restore from a signal handler, saved before echo is first turned off
+#[cfg(unix)]
+static ORIGINAL_TERMINAL: std::sync::OnceLock<libc::termios> = std::sync::OnceLock::new();
+
+#[cfg(unix)]
+extern "C" fn restore_terminal_and_exit(signal: libc::c_int) {
+    // only async-signal-safe calls: restore the terminal, then die of the signal as if unhandled
+    unsafe {
+        if let Some(original) = ORIGINAL_TERMINAL.get() {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, original);
+        }
+        libc::signal(signal, libc::SIG_DFL);
+        libc::raise(signal);
+    }
+}
+
+impl HiddenInput {
+    #[cfg(unix)]
+    fn new() -> Result<Self, String> {
+        let mut original = std::mem::MaybeUninit::<libc::termios>::uninit();
+        if unsafe { libc::tcgetattr(libc::STDIN_FILENO, original.as_mut_ptr()) } != 0 {
+            return Err(io::Error::last_os_error().to_string());
+        }
+        let original = unsafe { original.assume_init() };
+        if ORIGINAL_TERMINAL.set(original).is_ok() {
+            let handler = restore_terminal_and_exit as extern "C" fn(libc::c_int);
+            for signal in [libc::SIGINT, libc::SIGTERM, libc::SIGHUP] {
+                unsafe { libc::signal(signal, handler as libc::sighandler_t) };
+            }
+        }
+        let mut hidden = original;
+        hidden.c_lflag &= !libc::ECHO;
+        if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &hidden) } != 0 {
+            return Err(io::Error::last_os_error().to_string());
+        }
+        Ok(HiddenInput { original })
+    }
+
+    #[cfg(not(unix))]
+    fn new() -> Result<Self, String> {
+        Err("turning off echo is not supported on this platform".to_string())
+    }
+}
+
+impl Drop for HiddenInput {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.original);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use dotenv_lib::EnvDocument;
+
+    use super::ask;
+
+    fn answer(example: &str, input: &str) -> (Result<String, String>, String) {
+        let mut document = EnvDocument::parse(example).unwrap();
+        let key = document.entries().next().unwrap().key.clone();
+        let entry = document.get_mut(&key).unwrap();
+        let mut output = Vec::new();
+        let result = ask(entry, &mut Cursor::new(input), &mut output, false);
+        (
+            result.map(|_| entry.value.clone()),
+            String::from_utf8(output).unwrap(),
+        )
+    }
+
+    #[test]
+    fn prompt_with_description_and_default() {
+        let (value, output) = answer("# @description HTTP port\n# @type port\nPORT=8080\n", "\n");
+        assert_eq!(value.unwrap(), "8080");
+        assert_eq!(output, "# HTTP port\nPORT [8080]: ");
+    }
+
+    #[test]
+    fn reprompt_until_valid() {
+        let (value, output) = answer("# @type port\n# @secret\nPORT=changeme\n", "\nabc\n443\n");
+        assert_eq!(value.unwrap(), "443");
+        assert!(output.contains("PORT needs a value"));
+        assert!(output.contains("invalid value"));
+        let (value, _) = answer("TOKEN=changeme\n", "");
+        assert!(value.is_err());
+    }
+
+    #[test]
+    fn generate_values() {
+        let (value, output) = answer("# @generate hex:32\nSESSION_KEY=changeme\n", "");
+        assert_eq!(value.unwrap().len(), 64);
+        assert_eq!(output, "SESSION_KEY: generated\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn new_env_file_is_private() {
+        use std::fs;
+        use std::os::unix::fs::PermissionsExt;
+
+        let (env, example) = ("./tests/TestInit.env", "./tests/TestInit.env.example");
+        let _ = fs::remove_file(env);
+        fs::write(example, "# @generate hex:16\nSESSION_KEY=changeme\n").unwrap();
+        let argv = ["--env", env, "--example", example].map(String::from);
+        super::run(argv.to_vec()).unwrap();
+        let mode = fs::metadata(env).unwrap().permissions().mode() & 0o777;
+        fs::remove_file(env).unwrap();
+        fs::remove_file(example).unwrap();
+        assert_eq!(mode, 0o600);
+    }
+}
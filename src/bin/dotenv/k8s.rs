@@ -0,0 +1,66 @@
+// `dotenv k8s`: write Kubernetes ConfigMap and Secret manifests from an env file, or read them back
+use std::process::ExitCode;
+
+use dotenv_lib::kubernetes_manifests;
+
+use crate::args::{Args, Opt};
+use crate::load_env;
+
+pub const USAGE: &str = "dotenv k8s --name NAME [--namespace NS] [--env PATH]
+dotenv k8s --import MANIFEST
+    Prints a ConfigMap holding the variables in the env file, and a Secret holding the
+    base64 encoded values of those annotated @secret or named like credentials. With
+    --import, prints the ConfigMaps and Secrets in MANIFEST as an env file instead, which
+    needs the `yaml` crate feature.
+    --name NAME         name of the ConfigMap and Secret
+    --namespace NS      namespace to put them in
+    --env PATH          env file to read (default: .env)
+    --import MANIFEST   YAML manifests to read";
+
+const OPTS: &[Opt] = &[
+    Opt {
+        names: &["--name"],
+        takes_value: true,
+    },
+    Opt {
+        names: &["--namespace"],
+        takes_value: true,
+    },
+    Opt {
+        names: &["--env"],
+        takes_value: true,
+    },
+    Opt {
+        names: &["--import"],
+        takes_value: true,
+    },
+];
+
+#[cfg(feature = "yaml")]
+fn import(path: &str) -> Result<String, String> {
+    let contents =
+        std::fs::read_to_string(path).map_err(|err| format!("cannot read {path}: {err}"))?;
+    let env = dotenv_lib::kubernetes_to_env_map(&contents).map_err(|err| err.to_string())?;
+    dotenv_lib::env_map_to_env_string(&env).map_err(|err| err.to_string())
+}
+
+#[cfg(not(feature = "yaml"))]
+fn import(_path: &str) -> Result<String, String> {
+    Err("dotenv was built without the `yaml` feature".to_string())
+}
+
+pub fn run(argv: Vec<String>) -> Result<ExitCode, String> {
+    let args = Args::parse(argv, OPTS)?;
+    if let Some(path) = args.value("--import") {
+        print!("{}", import(path)?);
+        return Ok(ExitCode::SUCCESS);
+    }
+    let name = args
+        .value("--name")
+        .ok_or_else(|| format!("--name is required\n  {USAGE}"))?;
+    let env = load_env(args.value("--env").unwrap_or(".env"))?;
+    let manifests = kubernetes_manifests(&env, name, args.value("--namespace"))
+        .map_err(|err| err.to_string())?;
+    print!("{manifests}");
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,67 @@
+// `dotenv lint`: check env files for common problems
+use std::fs;
+use std::process::ExitCode;
+
+use dotenv_lib::{LintConfig, LintDiagnostic, LintRule, lint_env, lint_json};
+
+use crate::args::{Args, Opt};
+
+pub const USAGE: &str = "dotenv lint [--disable RULE]... [--format text|json] [PATH]...
+    Checks each env file (default: .env) and prints the problems found with their line and
+    column, exiting with 1 if there are any. Rules: syntax, duplicate-key, lowercase-key,
+    unquoted-space, trailing-whitespace, mixed-line-endings, missing-final-newline,
+    unordered-key, shell-quote. A `# dotenv-lint-disable-next-line RULE` comment suppresses
+    a rule on the next line.
+    --disable RULE    skip a rule, may be repeated
+    --format FORMAT   text (default) or json";
+
+const OPTS: &[Opt] = &[
+    Opt {
+        names: &["--disable"],
+        takes_value: true,
+    },
+    Opt {
+        names: &["--format"],
+        takes_value: true,
+    },
+];
+
+pub fn run(argv: Vec<String>) -> Result<ExitCode, String> {
+    let args = Args::parse(argv, OPTS)?;
+    let mut config = LintConfig::new();
+    for name in args.values("--disable") {
+        config = config.disable(name.parse::<LintRule>()?);
+    }
+    let format = args.value("--format").unwrap_or("text");
+    if !matches!(format, "text" | "json") {
+        return Err(format!("unknown format '{format}', expected text or json"));
+    }
+    let paths = match args.positional.as_slice() {
+        [] => vec![".env".to_string()],
+        paths => paths.to_vec(),
+    };
+    let mut results: Vec<(String, Vec<LintDiagnostic>)> = Vec::new();
+    for path in paths {
+        let contents =
+            fs::read_to_string(&path).map_err(|err| format!("cannot read {path}: {err}"))?;
+        let diagnostics = lint_env(&contents, &config);
+        results.push((path, diagnostics));
+    }
+    if format == "json" {
+        println!(
+            "{}",
+            lint_json(results.iter().map(|(p, d)| (p.as_str(), d.as_slice())))
+        );
+    } else {
+        for (path, diagnostics) in &results {
+            for diagnostic in diagnostics {
+                println!("{path}:{diagnostic}");
+            }
+        }
+    }
+    Ok(if results.iter().any(|(_, d)| !d.is_empty()) {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
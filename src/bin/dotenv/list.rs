@@ -0,0 +1,87 @@
+// `dotenv list`: print every key and value
+use std::process::ExitCode;
+
+use dotenv_lib::{EnvEntry, ParsedEnv, looks_like_secret_key};
+
+use crate::args::{Args, Opt};
+use crate::load_env;
+
+pub const USAGE: &str = "dotenv list [--env PATH] [--mask] [--format text|json]
+    Prints every key and its value, one KEY=value per line or as a JSON object.
+    --env PATH        env file to read (default: .env)
+    --mask            hide the values of @secret keys and keys named like credentials
+    --format FORMAT   text (default) or json";
+
+const OPTS: &[Opt] = &[
+    Opt {
+        names: &["--env"],
+        takes_value: true,
+    },
+    Opt {
+        names: &["--mask"],
+        takes_value: false,
+    },
+    Opt {
+        names: &["--format"],
+        takes_value: true,
+    },
+];
+
+const MASK: &str = "********";
+
+pub fn run(argv: Vec<String>) -> Result<ExitCode, String> {
+    let args = Args::parse(argv, OPTS)?;
+    let env = load_env(args.value("--env").unwrap_or(".env"))?;
+    let env = if args.flag("--mask") {
+        masked(&env)
+    } else {
+        env
+    };
+    match args.value("--format").unwrap_or("text") {
+        "text" => env
+            .entries()
+            .iter()
+            .for_each(|e| println!("{}={}", e.key, e.value)),
+        "json" => println!("{}", env.to_json()),
+        other => return Err(format!("unknown format '{other}', expected text or json")),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// replaces the values of secret keys with a fixed mask
+fn masked(env: &ParsedEnv) -> ParsedEnv {
+    ParsedEnv::from_entries(
+        env.entries()
+            .iter()
+            .map(|e| {
+                let secret = e.annotations.is_secret() || looks_like_secret_key(&e.key);
+                EnvEntry {
+                    value: if secret {
+                        MASK.to_string()
+                    } else {
+                        e.value.clone()
+                    },
+                    ..e.clone()
+                }
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use dotenv_lib::parse_env;
+
+    use super::masked;
+
+    #[test]
+    fn mask_secret_values() {
+        let env = parse_env("PORT=80\n# @secret\nDSN=x\nAPI_TOKEN=abc\n".to_string()).unwrap();
+        let values: Vec<String> = masked(&env)
+            .entries()
+            .iter()
+            .map(|e| e.value.clone())
+            .collect();
+        assert_eq!(values, vec!["80", "********", "********"]);
+    }
+}
@@ -0,0 +1,98 @@
+// the `dotenv` command-line tool
+use std::fs;
+use std::process::ExitCode;
+
+use dotenv_lib::{EnvDocument, ParsedEnv, parse_env};
+
+mod args;
+mod check;
+mod convert;
+mod docker;
+mod example;
+mod export;
+mod fmt;
+mod get;
+mod github;
+mod init;
+mod k8s;
+mod lint;
+mod list;
+mod portability;
+mod run;
+mod set;
+mod sync;
+mod unset;
+
+/// reads and parses an env file, describing any failure with the path
+pub fn load_env(path: &str) -> Result<ParsedEnv, String> {
+    let contents = fs::read_to_string(path).map_err(|err| format!("cannot read {path}: {err}"))?;
+    parse_env(contents).map_err(|err| format!("cannot parse {path}: {err}"))
+}
+
+/// reads and parses an env file keeping its comments and layout, describing any failure with the path
+pub fn load_document(path: &str) -> Result<EnvDocument, String> {
+    let contents = fs::read_to_string(path).map_err(|err| format!("cannot read {path}: {err}"))?;
+    EnvDocument::parse(&contents).map_err(|err| format!("cannot parse {path}: {err}"))
+}
+
+fn usage() -> String {
+    [
+        "usage:",
+        check::USAGE,
+        convert::USAGE,
+        docker::USAGE,
+        example::USAGE,
+        export::USAGE,
+        fmt::USAGE,
+        get::USAGE,
+        github::USAGE,
+        init::USAGE,
+        k8s::USAGE,
+        lint::USAGE,
+        list::USAGE,
+        portability::USAGE,
+        run::USAGE,
+        set::USAGE,
+        sync::USAGE,
+        unset::USAGE,
+    ]
+    .join("\n  ")
+}
+
+fn main() -> ExitCode {
+    let mut argv = std::env::args().skip(1);
+    let command = argv.next();
+    let rest: Vec<String> = argv.collect();
+    let result = match command.as_deref() {
+        Some("check") => check::run(rest),
+        Some("convert") => convert::run(rest),
+        Some("docker") => docker::run(rest),
+        Some("example") => example::run(rest),
+        Some("export") => export::run(rest),
+        Some("fmt") => fmt::run(rest),
+        Some("get") => get::run(rest),
+        Some("github") => github::run(rest),
+        Some("init") => init::run(rest),
+        Some("k8s") => k8s::run(rest),
+        Some("lint") => lint::run(rest),
+        Some("list") => list::run(rest),
+        Some("portability") => portability::run(rest),
+        Some("run") => run::run(rest),
+        Some("set") => set::run(rest),
+        Some("sync") => sync::run(rest),
+        Some("unset") => unset::run(rest),
+        Some("help" | "--help" | "-h") => {
+            println!("{}", usage());
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(other) => Err(format!("unknown command '{other}'\n{}", usage())),
+        None => Err(usage()),
+    };
+    match result {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("dotenv: {err}");
+            ExitCode::from(2)
+        }
+    }
+}
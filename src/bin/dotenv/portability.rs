@@ -0,0 +1,49 @@
+// `dotenv portability`: report the keys other dotenv implementations would read differently
+use std::fs;
+use std::process::ExitCode;
+
+use dotenv_lib::{Dialect, portability_report};
+
+use crate::args::{Args, Opt};
+
+pub const USAGE: &str = "dotenv portability [--dialect NAME]... [PATH]...
+    Reads each env file (default: .env) with this crate's parser and with the rules of other
+    dotenv implementations, and prints every key that does not read as the same value under
+    all of them, and every parser that cannot read the file. Exits with 1 if there are any.
+    --dialect NAME    compare with node, python, ruby, dotenvy or compose, may be repeated
+                      (default: all of them)";
+
+const OPTS: &[Opt] = &[Opt {
+    names: &["--dialect"],
+    takes_value: true,
+}];
+
+pub fn run(argv: Vec<String>) -> Result<ExitCode, String> {
+    let args = Args::parse(argv, OPTS)?;
+    let dialects = match args.values("--dialect").as_slice() {
+        [] => Dialect::PRESETS.to_vec(),
+        names => names
+            .iter()
+            .map(|name| name.parse())
+            .collect::<Result<_, _>>()?,
+    };
+    let paths = match args.positional.as_slice() {
+        [] => vec![".env".to_string()],
+        paths => paths.to_vec(),
+    };
+    let mut portable = true;
+    for path in paths {
+        let contents =
+            fs::read_to_string(&path).map_err(|err| format!("cannot read {path}: {err}"))?;
+        let report = portability_report(&contents, &dialects);
+        for line in report.to_string().lines() {
+            println!("{path}:{line}");
+        }
+        portable &= report.is_portable();
+    }
+    Ok(if portable {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
@@ -0,0 +1,158 @@
+// `dotenv run`: launch a program with the variables from one or more env files
+use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
+use std::fs;
+#[cfg(not(unix))]
+use std::process::ExitStatus;
+use std::process::{Command, ExitCode};
+
+use dotenv_lib::process_dot_env;
+
+use crate::args::{Args, Opt};
+
+pub const USAGE: &str =
+    "dotenv run [-f PATH]... [--override] [--set KEY=VALUE]... [--clean] -- COMMAND [ARGS]...
+    Runs COMMAND with the variables from the env files added to its environment. Later
+    files take precedence over earlier ones. On unix COMMAND replaces dotenv, so it gets
+    signals directly; elsewhere it runs as a child and dotenv exits with its exit code.
+    --file PATH, -f      env file to load, may be repeated (default: .env)
+    --override           let file values replace variables already in the environment
+    --set KEY=VALUE      set a variable, taking precedence over every file
+    --clean              start from an empty environment instead of inheriting one";
+
+const OPTS: &[Opt] = &[
+    Opt {
+        names: &["--file", "-f"],
+        takes_value: true,
+    },
+    Opt {
+        names: &["--override"],
+        takes_value: false,
+    },
+    Opt {
+        names: &["--set"],
+        takes_value: true,
+    },
+    Opt {
+        names: &["--clean"],
+        takes_value: false,
+    },
+];
+
+pub fn run(argv: Vec<String>) -> Result<ExitCode, String> {
+    let args = Args::parse(argv, OPTS)?;
+    let mut command = args.positional.iter().chain(&args.rest);
+    let program = command
+        .next()
+        .ok_or_else(|| "no command given to run".to_string())?;
+    let paths = match args.values("--file") {
+        paths if paths.is_empty() => vec![".env"],
+        paths => paths,
+    };
+    let files = paths
+        .into_iter()
+        .map(|path| {
+            let contents =
+                fs::read_to_string(path).map_err(|err| format!("cannot read {path}: {err}"))?;
+            process_dot_env(contents).map_err(|err| format!("cannot parse {path}: {err}"))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    let sets = args
+        .values("--set")
+        .into_iter()
+        .map(|set| {
+            set.split_once('=')
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .ok_or_else(|| format!("expected KEY=VALUE for --set, found '{set}'"))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let mut child = Command::new(program);
+    child.args(command);
+    let inherited: HashMap<OsString, OsString> = if args.flag("--clean") {
+        child.env_clear();
+        HashMap::new()
+    } else {
+        std::env::vars_os().collect()
+    };
+    child.envs(child_vars(files, sets, &inherited, args.flag("--override")));
+    exec(child, program)
+}
+
+/// replaces this process with the program, so signals such as Ctrl-C reach it directly. only
+/// returns when the program could not be started
+#[cfg(unix)]
+fn exec(mut child: Command, program: &str) -> Result<ExitCode, String> {
+    use std::os::unix::process::CommandExt;
+    let err = child.exec();
+    Err(format!("cannot run {program}: {err}"))
+}
+
+/// runs the program as a child where a process cannot be replaced, passing its exit code through
+#[cfg(not(unix))]
+fn exec(mut child: Command, program: &str) -> Result<ExitCode, String> {
+    let status = child
+        .status()
+        .map_err(|err| format!("cannot run {program}: {err}"))?;
+    Ok(exit_code(status))
+}
+
+/// merges the env files and `--set` values into the variables to give the child. later files win
+/// over earlier ones, and variables already inherited are kept unless `override_inherited` is set
+fn child_vars(
+    files: Vec<HashMap<String, String>>,
+    sets: Vec<(String, String)>,
+    inherited: &HashMap<OsString, OsString>,
+    override_inherited: bool,
+) -> HashMap<String, String> {
+    let mut vars: HashMap<String, String> = files
+        .into_iter()
+        .flatten()
+        .filter(|(key, _)| override_inherited || !inherited.contains_key(OsStr::new(key)))
+        .collect();
+    vars.extend(sets);
+    vars
+}
+
+/// passes the child's exit code through. codes that do not fit in a byte, which Windows allows,
+/// become 1 so a failure never reads as success
+#[cfg(not(unix))]
+fn exit_code(status: ExitStatus) -> ExitCode {
+    match status.code() {
+        Some(code) => ExitCode::from(u8::try_from(code).unwrap_or(1)),
+        None => ExitCode::FAILURE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::ffi::OsString;
+
+    use super::child_vars;
+
+    fn map(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn later_files_and_sets_take_precedence() {
+        let files = vec![
+            map(&[("A", "env"), ("B", "env"), ("HOME", "file")]),
+            map(&[("B", "local")]),
+        ];
+        let sets = vec![("A".to_string(), "set".to_string())];
+        let inherited = HashMap::from([(OsString::from("HOME"), OsString::from("/root"))]);
+        assert_eq!(
+            child_vars(files.clone(), sets.clone(), &inherited, false),
+            map(&[("A", "set"), ("B", "local")])
+        );
+        assert_eq!(
+            child_vars(files, sets, &inherited, true),
+            map(&[("A", "set"), ("B", "local"), ("HOME", "file")])
+        );
+    }
+}
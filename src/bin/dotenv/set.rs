@@ -0,0 +1,68 @@
+// `dotenv set`: set the value of a key in place
+use std::path::Path;
+use std::process::ExitCode;
+
+use dotenv_lib::EnvDocument;
+
+use crate::args::{Args, Opt};
+use crate::load_document;
+
+pub const USAGE: &str = "dotenv set [--env PATH] KEY [--] VALUE
+    Sets KEY to VALUE, quoting it as needed. An existing entry is changed in place and a new
+    key is added at the end, leaving the rest of the file as it was. Put -- before a VALUE
+    that starts with -, such as a negative number.
+    --env PATH    env file to edit, created if missing (default: .env)";
+
+const OPTS: &[Opt] = &[Opt {
+    names: &["--env"],
+    takes_value: true,
+}];
+
+pub fn run(argv: Vec<String>) -> Result<ExitCode, String> {
+    let args = Args::parse(argv, OPTS)?;
+    let Some((key, value)) = key_and_value(&args) else {
+        return Err(format!("expected a key and a value\n  {USAGE}"));
+    };
+    let path = args.value("--env").unwrap_or(".env");
+    let mut document = if Path::new(path).exists() {
+        load_document(path)?
+    } else {
+        EnvDocument::default()
+    };
+    document
+        .set(key, value)
+        .map_err(|err| format!("cannot set {key}: {err}"))?;
+    document
+        .save(path)
+        .map_err(|err| format!("cannot write {path}: {err}"))?;
+    Ok(ExitCode::SUCCESS)
+}
+
+/// takes KEY and VALUE from the positional arguments, where VALUE may also follow a `--`
+fn key_and_value(args: &Args) -> Option<(&str, &str)> {
+    match (args.positional.as_slice(), args.rest.as_slice()) {
+        ([key, value], []) | ([key], [value]) => Some((key, value)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{OPTS, key_and_value};
+    use crate::args::Args;
+
+    fn parse(args: &[&str]) -> Result<Args, String> {
+        Args::parse(args.iter().map(|a| a.to_string()), OPTS)
+    }
+
+    #[test]
+    fn value_after_separator() {
+        let args = parse(&["NEG", "--", "-5"]).unwrap();
+        assert_eq!(key_and_value(&args), Some(("NEG", "-5")));
+        let args = parse(&["--env", "a.env", "PORT", "8080"]).unwrap();
+        assert_eq!(key_and_value(&args), Some(("PORT", "8080")));
+        assert!(parse(&["NEG", "-5"]).is_err());
+        let args = parse(&["A", "1", "--", "2"]).unwrap();
+        assert_eq!(key_and_value(&args), None);
+    }
+}
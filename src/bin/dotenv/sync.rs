@@ -0,0 +1,54 @@
+// `dotenv sync`: add keys from `.env.example` that a `.env` file is missing
+use std::path::Path;
+use std::process::ExitCode;
+
+use dotenv_lib::EnvDocument;
+
+use crate::args::{Args, Opt};
+use crate::load_document;
+
+pub const USAGE: &str = "dotenv sync [--env PATH] [--example PATH] [--dry-run]
+    Appends keys from the example that the env file does not set, along with their
+    comments and @default values, and lists the keys added. Existing lines are kept.
+    --env PATH        env file to update, created if missing (default: .env)
+    --example PATH    example file to copy keys from (default: .env.example)
+    --dry-run         list the keys that would be added without writing";
+
+const OPTS: &[Opt] = &[
+    Opt {
+        names: &["--env"],
+        takes_value: true,
+    },
+    Opt {
+        names: &["--example"],
+        takes_value: true,
+    },
+    Opt {
+        names: &["--dry-run"],
+        takes_value: false,
+    },
+];
+
+pub fn run(argv: Vec<String>) -> Result<ExitCode, String> {
+    let args = Args::parse(argv, OPTS)?;
+    let path = args.value("--env").unwrap_or(".env");
+    let example = load_document(args.value("--example").unwrap_or(".env.example"))?;
+    let mut env = if Path::new(path).exists() {
+        load_document(path)?
+    } else {
+        EnvDocument::default()
+    };
+    let added = env.sync_from_example(&example)?;
+    if added.is_empty() {
+        println!("{path} already has every key in the example");
+        return Ok(ExitCode::SUCCESS);
+    }
+    if !args.flag("--dry-run") {
+        env.save(path)
+            .map_err(|err| format!("cannot write {path}: {err}"))?;
+    }
+    for key in added {
+        println!("added: {key}");
+    }
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,34 @@
+// `dotenv unset`: remove a key in place
+use std::process::ExitCode;
+
+use crate::args::{Args, Opt};
+use crate::load_document;
+
+pub const USAGE: &str = "dotenv unset [--env PATH] KEY...
+    Removes each KEY along with its annotation comments, leaving the rest of the file as
+    it was. Keys that are not set are ignored.
+    --env PATH    env file to edit (default: .env)";
+
+const OPTS: &[Opt] = &[Opt {
+    names: &["--env"],
+    takes_value: true,
+}];
+
+pub fn run(argv: Vec<String>) -> Result<ExitCode, String> {
+    let args = Args::parse(argv, OPTS)?;
+    if args.positional.is_empty() {
+        return Err(format!("expected at least one key\n  {USAGE}"));
+    }
+    let path = args.value("--env").unwrap_or(".env");
+    let mut document = load_document(path)?;
+    let mut removed = false;
+    for key in &args.positional {
+        removed |= document.remove(key);
+    }
+    if removed {
+        document
+            .save(path)
+            .map_err(|err| format!("cannot write {path}: {err}"))?;
+    }
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,194 @@
+// comparing a local `.env` file with the committed `.env.example` it is based on
+use std::fmt;
+
+use crate::ParsedEnv;
+
+/// The differences between a `.env` file and its `.env.example`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CheckReport {
+    /// keys in the example that the `.env` file does not set
+    pub missing: Vec<String>,
+    /// keys in the `.env` file that the example does not declare
+    pub extra: Vec<String>,
+    /// keys whose values in the `.env` file still look like placeholders, with the line they are on
+    pub placeholders: Vec<(String, u64)>,
+}
+
+impl CheckReport {
+    /// Returns true when nothing is missing, extra or left as a placeholder.
+    pub fn is_clean(&self) -> bool {
+        self.missing.is_empty() && self.extra.is_empty() && self.placeholders.is_empty()
+    }
+}
+
+impl fmt::Display for CheckReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_clean() {
+            return writeln!(f, "env file matches the example");
+        }
+        for key in &self.missing {
+            writeln!(f, "missing: {key} is in the example but not set")?;
+        }
+        for key in &self.extra {
+            writeln!(f, "extra: {key} is set but not in the example")?;
+        }
+        for (key, line) in &self.placeholders {
+            writeln!(
+                f,
+                "placeholder: {key} on line {line} still holds a placeholder value"
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns true for values such as `changeme`, `TODO`, `xxx`, `<your-token>` or `your_api_key`
+/// that are left in place of a real value.
+pub fn looks_like_placeholder(value: &str) -> bool {
+    let lower = value.trim().to_ascii_lowercase();
+    let known = [
+        "changeme",
+        "change_me",
+        "change-me",
+        "replaceme",
+        "replace_me",
+        "replace-me",
+        "placeholder",
+        "todo",
+        "tbd",
+        "fixme",
+        "secret",
+        "password",
+        "...",
+    ];
+    known.contains(&lower.as_str())
+        || (lower.len() >= 3 && lower.chars().all(|c| c == 'x'))
+        || (lower.starts_with('<') && lower.ends_with('>'))
+        || lower.starts_with("your_")
+        || lower.starts_with("your-")
+}
+
+/// Returns true for keys whose names suggest they hold credentials, such as `DB_PASSWORD`,
+/// `GITHUB_TOKEN` or `STRIPE_SECRET_KEY`.
+pub fn looks_like_secret_key(key: &str) -> bool {
+    let upper = key.to_ascii_uppercase();
+    [
+        "SECRET",
+        "PASSWORD",
+        "PASSWD",
+        "TOKEN",
+        "API_KEY",
+        "APIKEY",
+        "PRIVATE_KEY",
+        "CREDENTIAL",
+    ]
+    .iter()
+    .any(|word| upper.contains(word))
+}
+
+/// Compares a parsed `.env` file with its `.env.example`.
+///
+/// Keys the example gives a `@default` annotation are not reported as missing. A value counts as a
+/// placeholder when it looks like one, or when it was copied unchanged from an example key annotated
+/// `@secret`.
+/// ```rust
+/// # use dotenv_lib::{check_against_example, parse_env};
+/// let example = parse_env("# @secret\nTOKEN=abc\nHOST=localhost\n".to_string()).unwrap();
+/// let env = parse_env("TOKEN=abc\nDEBUG=1\n".to_string()).unwrap();
+/// let report = check_against_example(&env, &example);
+/// assert_eq!(report.missing, vec!["HOST"]);
+/// assert_eq!(report.extra, vec!["DEBUG"]);
+/// assert_eq!(report.placeholders, vec![("TOKEN".to_string(), 1)]);
+/// ```
+pub fn check_against_example(env: &ParsedEnv, example: &ParsedEnv) -> CheckReport {
+    let missing = example
+        .entries()
+        .iter()
+        .filter(|e| !env.contains_key(&e.key) && e.annotations.default_value().is_none())
+        .map(|e| e.key.clone())
+        .collect();
+    let extra = env
+        .entries()
+        .iter()
+        .filter(|e| !example.contains_key(&e.key))
+        .map(|e| e.key.clone())
+        .collect();
+    let placeholders = env
+        .entries()
+        .iter()
+        .filter(|e| {
+            let copied_secret = example
+                .entry(&e.key)
+                .is_some_and(|x| x.annotations.is_secret() && x.value == e.value);
+            copied_secret || looks_like_placeholder(&e.value)
+        })
+        .map(|e| (e.key.clone(), e.line))
+        .collect();
+    CheckReport {
+        missing,
+        extra,
+        placeholders,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        CheckReport, check_against_example, looks_like_placeholder, looks_like_secret_key,
+        parse_env,
+    };
+
+    #[test]
+    fn placeholder_values() {
+        for value in ["changeme", "TODO", "xxxx", "<token>", "your-api-key", "..."] {
+            assert!(looks_like_placeholder(value), "{value}");
+        }
+        for value in ["8080", "postgres://db", "x", "production"] {
+            assert!(!looks_like_placeholder(value), "{value}");
+        }
+    }
+
+    #[test]
+    fn secret_keys() {
+        for key in [
+            "DB_PASSWORD",
+            "github_token",
+            "STRIPE_SECRET_KEY",
+            "OPENAI_API_KEY",
+        ] {
+            assert!(looks_like_secret_key(key), "{key}");
+        }
+        for key in ["PORT", "DATABASE_URL", "KEYBOARD_LAYOUT"] {
+            assert!(!looks_like_secret_key(key), "{key}");
+        }
+    }
+
+    #[test]
+    fn report_differences() {
+        let example = parse_env(
+            "PORT=8080\n# @default info\nLOG_LEVEL=info\nAPI_KEY=your_api_key\nHOST=localhost\n"
+                .to_string(),
+        )
+        .expect("error processing env file");
+        let env = parse_env("PORT=3000\nAPI_KEY=your_api_key\nEXTRA=1\n".to_string())
+            .expect("error processing env file");
+        let report = check_against_example(&env, &example);
+        assert_eq!(
+            report,
+            CheckReport {
+                missing: vec!["HOST".to_string()],
+                extra: vec!["EXTRA".to_string()],
+                placeholders: vec![("API_KEY".to_string(), 2)],
+            }
+        );
+        assert!(!report.is_clean());
+        assert!(report.to_string().contains("missing: HOST"));
+    }
+
+    #[test]
+    fn clean_report() {
+        let example = parse_env("PORT=8080\n".to_string()).unwrap();
+        let env = parse_env("PORT=80\n".to_string()).unwrap();
+        assert!(check_against_example(&env, &example).is_clean());
+    }
+}
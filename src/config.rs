@@ -0,0 +1,239 @@
+// support for `#[derive(EnvConfig)]`
+use std::fmt;
+use std::io::Error as IOError;
+
+use crate::{EnvError, ValueError};
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(IOError),
+    Env(EnvError),
+    Invalid(Vec<ValueError>),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(err) => write!(f, "I/O Error: {err}"),
+            ConfigError::Env(err) => write!(f, "Env parsing error: {err}"),
+            ConfigError::Invalid(errors) => {
+                write!(f, "Invalid configuration:")?;
+                errors.iter().try_for_each(|err| write!(f, "\n  {err}"))
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Io(err) => Some(err),
+            ConfigError::Env(err) => Some(err),
+            ConfigError::Invalid(_) => None,
+        }
+    }
+}
+
+impl From<IOError> for ConfigError {
+    fn from(value: IOError) -> Self {
+        ConfigError::Io(value)
+    }
+}
+
+impl From<EnvError> for ConfigError {
+    fn from(value: EnvError) -> Self {
+        ConfigError::Env(value)
+    }
+}
+
+// used by the code that `#[derive(EnvConfig)]` generates, not part of the public API
+#[doc(hidden)]
+pub mod __private {
+    use std::str::FromStr;
+
+    use crate::{ParsedEnv, ValueError};
+
+    pub struct Redacted;
+
+    impl std::fmt::Debug for Redacted {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("\"***\"")
+        }
+    }
+
+    /// the key a field is read from when it has no `#[env(key = "...")]`
+    pub fn field_key(field: &str) -> String {
+        crate::screaming_snake_case(field)
+    }
+
+    pub fn parse_bool(value: &str) -> Result<bool, String> {
+        crate::typed::parse_bool(value)
+            .ok_or_else(|| "expected one of 1/0, true/false, yes/no, on/off".to_string())
+    }
+
+    pub fn parse_from_str<T>(value: &str) -> Result<T, String>
+    where
+        T: FromStr,
+        T::Err: std::fmt::Display,
+    {
+        value.parse().map_err(|err: T::Err| err.to_string())
+    }
+
+    pub fn read_optional<T>(
+        env: &ParsedEnv,
+        key: &str,
+        default: Option<&str>,
+        parse: fn(&str) -> Result<T, String>,
+        errors: &mut Vec<ValueError>,
+    ) -> Option<T> {
+        match env.entry(key) {
+            Some(entry) => match parse(&entry.value) {
+                Ok(value) => Some(value),
+                Err(reason) => {
+                    errors.push(ValueError::invalid(entry, reason));
+                    None
+                }
+            },
+            None => {
+                let default = default?;
+                match parse(default) {
+                    Ok(value) => Some(value),
+                    Err(reason) => {
+                        errors.push(ValueError::InvalidDefault {
+                            key: key.to_string(),
+                            default: default.to_string(),
+                            reason,
+                        });
+                        None
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn read_required<T>(
+        env: &ParsedEnv,
+        key: &str,
+        default: Option<&str>,
+        parse: fn(&str) -> Result<T, String>,
+        errors: &mut Vec<ValueError>,
+    ) -> Option<T> {
+        if !env.contains_key(key) && default.is_none() {
+            errors.push(ValueError::Missing {
+                key: key.to_string(),
+            });
+            return None;
+        }
+        read_optional(env, key, default, parse, errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ConfigError, EnvConfig, ValueError, parse_env};
+
+    #[derive(EnvConfig)]
+    struct Config {
+        port: u16,
+        #[env(default = "localhost")]
+        host: String,
+        debug: bool,
+        #[env(key = "DATABASE_URL", secret)]
+        database: String,
+        workers: Option<u8>,
+    }
+
+    #[test]
+    fn read_config_with_defaults() {
+        let env = parse_env("PORT=8080\nDEBUG=on\nDATABASE_URL=postgres://db\n".to_string())
+            .expect("error processing env file");
+        let config = Config::from_parsed_env(&env).unwrap();
+        assert_eq!(config.port, 8080);
+        assert_eq!(config.host, "localhost");
+        assert!(config.debug);
+        assert_eq!(config.database, "postgres://db");
+        assert_eq!(config.workers, None);
+    }
+
+    /// every missing and invalid key is reported, not just the first
+    #[test]
+    fn report_every_error() {
+        let env = parse_env("PORT=http\nWORKERS=many\n".to_string()).unwrap();
+        match Config::from_parsed_env(&env) {
+            Err(ConfigError::Invalid(errors)) => {
+                let keys: Vec<&str> = errors
+                    .iter()
+                    .map(|err| match err {
+                        ValueError::Missing { key }
+                        | ValueError::Invalid { key, .. }
+                        | ValueError::InvalidDefault { key, .. } => key.as_str(),
+                    })
+                    .collect();
+                assert_eq!(keys, vec!["PORT", "DEBUG", "DATABASE_URL", "WORKERS"]);
+            }
+            other => panic!("Did not return correct error: {other:?}"),
+        }
+    }
+
+    /// defaults the macro cannot check are reported like any other invalid value
+    #[test]
+    fn report_invalid_default() {
+        #[derive(EnvConfig)]
+        struct Server {
+            #[env(default = "localhost")]
+            bind: std::net::IpAddr,
+        }
+        let env = parse_env("OTHER=1\n".to_string()).unwrap();
+        match Server::from_parsed_env(&env) {
+            Err(ConfigError::Invalid(errors)) => assert_eq!(
+                errors,
+                vec![ValueError::InvalidDefault {
+                    key: "BIND".to_string(),
+                    default: "localhost".to_string(),
+                    reason: "invalid IP address syntax".to_string(),
+                }]
+            ),
+            other => panic!("Did not return correct error: {other:?}"),
+        }
+    }
+
+    /// keys are named the same way as by the serde deserializer
+    #[test]
+    #[allow(non_snake_case)]
+    fn camel_case_fields_use_screaming_snake_keys() {
+        #[derive(EnvConfig)]
+        struct Config {
+            databaseUrl: String,
+        }
+        let env = parse_env("DATABASE_URL=postgres://db\n".to_string()).unwrap();
+        let config = Config::from_parsed_env(&env).unwrap();
+        assert_eq!(config.databaseUrl, "postgres://db");
+    }
+
+    #[test]
+    fn debug_redacts_secrets() {
+        let env = parse_env("PORT=1\nDEBUG=0\nDATABASE_URL=hunter2\n".to_string()).unwrap();
+        let config = Config::from_parsed_env(&env).unwrap();
+        let debug = format!("{config:?}");
+        assert!(debug.contains("port: 1"));
+        assert!(!debug.contains("hunter2"));
+        assert!(debug.contains("database: \"***\""));
+    }
+
+    #[test]
+    fn from_env_file_reads_file() {
+        #[derive(EnvConfig)]
+        struct Greeting {
+            #[env(key = "Hello")]
+            hello: String,
+            nice_to: String,
+        }
+        let greeting = Greeting::from_env_file("tests/Test.env").expect("error reading config");
+        assert_eq!(greeting.hello, "World");
+        assert_eq!(greeting.nice_to, "meet you");
+        assert!(matches!(
+            Greeting::from_env_file("tests/Missing.env"),
+            Err(ConfigError::Io(_))
+        ));
+    }
+}
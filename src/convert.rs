@@ -0,0 +1,441 @@
+// converting between env files and flat JSON, YAML and TOML objects
+use std::collections::BTreeMap;
+use std::fmt;
+
+#[cfg(any(feature = "json", feature = "yaml", feature = "toml"))]
+use crate::NESTED_SEPARATOR;
+use crate::shell::is_variable_name;
+use crate::{EnvMap, quote_value};
+
+#[derive(Debug, PartialEq)]
+pub enum ConvertError {
+    /// the input could not be read as the given format, or was not an object at the top level
+    Parse {
+        format: &'static str,
+        message: String,
+    },
+    /// a value cannot be written in the output format
+    Unrepresentable { key: String, reason: String },
+    /// the env map could not be written as the given format
+    Serialize {
+        format: &'static str,
+        message: String,
+    },
+}
+
+impl fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConvertError::Parse { format, message } => write!(f, "Invalid {format}: {message}"),
+            ConvertError::Unrepresentable { key, reason } => {
+                write!(f, "Cannot convert key '{key}': {reason}")
+            }
+            ConvertError::Serialize { format, message } => {
+                write!(f, "Cannot write {format}: {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConvertError {}
+
+/// rejects keys that an env file cannot hold, as no parser would read them back
+fn check_key(key: &str) -> Result<(), ConvertError> {
+    if is_variable_name(key) {
+        Ok(())
+    } else {
+        Err(ConvertError::Unrepresentable {
+            key: key.to_string(),
+            reason: "not a valid variable name".to_string(),
+        })
+    }
+}
+
+/// How arrays are flattened into env values.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ArrayStyle {
+    /// join the items with commas into one value, which is how the `serde` feature reads sequences,
+    /// falling back to `Indexed` for arrays that hold objects or arrays
+    #[default]
+    Joined,
+    /// give each item its own key, so `HOSTS: [a, b]` becomes `HOSTS__0=a` and `HOSTS__1=b`
+    Indexed,
+}
+
+/// a format-independent document tree, so each format only needs to be converted into it
+#[cfg(any(feature = "json", feature = "yaml", feature = "toml"))]
+enum Node {
+    // TOML has no null, so only JSON and YAML produce this
+    #[cfg_attr(not(any(feature = "json", feature = "yaml")), allow(dead_code))]
+    Null,
+    Scalar(String),
+    Array(Vec<Node>),
+    Object(Vec<(String, Node)>),
+}
+
+#[cfg(any(feature = "json", feature = "yaml", feature = "toml"))]
+impl Node {
+    /// flattens the tree into `map`, joining nested keys with `__`
+    fn flatten(self, key: &str, arrays: ArrayStyle, map: &mut EnvMap) -> Result<(), ConvertError> {
+        let nested = |child: &str| {
+            if key.is_empty() {
+                child.to_string()
+            } else {
+                format!("{key}{NESTED_SEPARATOR}{child}")
+            }
+        };
+        match self {
+            Node::Null => {}
+            Node::Scalar(value) => {
+                map.insert(key.to_string(), value);
+            }
+            Node::Object(members) => {
+                for (child, node) in members {
+                    node.flatten(&nested(&child), arrays, map)?;
+                }
+            }
+            Node::Array(items) => {
+                let scalars = items.iter().all(|i| matches!(i, Node::Scalar(_)));
+                if arrays == ArrayStyle::Joined && scalars {
+                    let mut joined = Vec::new();
+                    for item in items {
+                        if let Node::Scalar(value) = item {
+                            if value.contains(',') {
+                                return Err(ConvertError::Unrepresentable {
+                                    key: key.to_string(),
+                                    reason: format!("list item '{value}' contains a comma"),
+                                });
+                            }
+                            joined.push(value);
+                        }
+                    }
+                    if !joined.is_empty() {
+                        map.insert(key.to_string(), joined.join(","));
+                    }
+                } else {
+                    for (index, item) in items.into_iter().enumerate() {
+                        item.flatten(&nested(&index.to_string()), arrays, map)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// flattens a parsed document, which must be an object at the top level and whose flattened
+    /// keys must all be variable names
+    fn into_env_map(
+        self,
+        format: &'static str,
+        arrays: ArrayStyle,
+    ) -> Result<EnvMap, ConvertError> {
+        if !matches!(self, Node::Object(_)) {
+            return Err(ConvertError::Parse {
+                format,
+                message: "expected an object at the top level".to_string(),
+            });
+        }
+        let mut map = EnvMap::new();
+        self.flatten("", arrays, &mut map)?;
+        let mut keys: Vec<&String> = map.keys().collect();
+        keys.sort();
+        for key in keys {
+            check_key(key)?;
+        }
+        Ok(map)
+    }
+}
+
+/// the entries of an env map sorted by key, so converted output is stable
+fn sorted(env: &EnvMap) -> BTreeMap<&str, &str> {
+    env.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect()
+}
+
+/// Writes an env map as the contents of a `.env` file with the keys in sorted order, quoting values
+/// so that [`process_dot_env`](crate::process_dot_env) reads them back unchanged.
+pub fn env_map_to_env_string(env: &EnvMap) -> Result<String, ConvertError> {
+    let mut contents = String::new();
+    for (key, value) in sorted(env) {
+        check_key(key)?;
+        let quoted = quote_value(value).map_err(|reason| ConvertError::Unrepresentable {
+            key: key.to_string(),
+            reason,
+        })?;
+        contents.push_str(&format!("{key}={quoted}\n"));
+    }
+    Ok(contents)
+}
+
+#[cfg(feature = "json")]
+impl From<serde_json::Value> for Node {
+    fn from(value: serde_json::Value) -> Self {
+        use serde_json::Value;
+        match value {
+            Value::Null => Node::Null,
+            Value::Bool(b) => Node::Scalar(b.to_string()),
+            Value::Number(n) => Node::Scalar(n.to_string()),
+            Value::String(s) => Node::Scalar(s),
+            Value::Array(items) => Node::Array(items.into_iter().map(Node::from).collect()),
+            Value::Object(members) => {
+                Node::Object(members.into_iter().map(|(k, v)| (k, v.into())).collect())
+            }
+        }
+    }
+}
+
+/// Reads a JSON object into an env map, flattening nested objects into `A__B` keys.
+/// ```rust
+/// # use dotenv_lib::{json_to_env_map, ArrayStyle};
+/// let env = json_to_env_map(r#"{"port": 80, "db": {"host": "x"}, "tags": ["a", "b"]}"#, ArrayStyle::Joined).unwrap();
+/// assert_eq!(env["port"], "80");
+/// assert_eq!(env["db__host"], "x");
+/// assert_eq!(env["tags"], "a,b");
+/// ```
+#[cfg(feature = "json")]
+pub fn json_to_env_map(contents: &str, arrays: ArrayStyle) -> Result<EnvMap, ConvertError> {
+    let value: serde_json::Value =
+        serde_json::from_str(contents).map_err(|err| ConvertError::Parse {
+            format: "JSON",
+            message: err.to_string(),
+        })?;
+    Node::from(value).into_env_map("JSON", arrays)
+}
+
+/// Writes an env map as a flat JSON object of strings, with the keys in sorted order.
+#[cfg(feature = "json")]
+pub fn env_map_to_json(env: &EnvMap) -> Result<String, ConvertError> {
+    serde_json::to_string_pretty(&sorted(env)).map_err(|err| ConvertError::Serialize {
+        format: "JSON",
+        message: err.to_string(),
+    })
+}
+
+#[cfg(feature = "yaml")]
+impl TryFrom<serde_yaml::Value> for Node {
+    type Error = ConvertError;
+
+    fn try_from(value: serde_yaml::Value) -> Result<Self, ConvertError> {
+        use serde_yaml::Value;
+        Ok(match value {
+            Value::Null => Node::Null,
+            Value::Bool(b) => Node::Scalar(b.to_string()),
+            Value::Number(n) => Node::Scalar(n.to_string()),
+            Value::String(s) => Node::Scalar(s),
+            Value::Sequence(items) => Node::Array(
+                items
+                    .into_iter()
+                    .map(Node::try_from)
+                    .collect::<Result<_, _>>()?,
+            ),
+            Value::Mapping(members) => Node::Object(
+                members
+                    .into_iter()
+                    .map(|(k, v)| {
+                        // YAML allows any value as a key, but only scalars can name a variable
+                        let key = match k {
+                            Value::String(s) => s,
+                            Value::Bool(b) => b.to_string(),
+                            Value::Number(n) => n.to_string(),
+                            _ => {
+                                return Err(ConvertError::Parse {
+                                    format: "YAML",
+                                    message: "mapping keys must be strings, numbers or booleans"
+                                        .to_string(),
+                                });
+                            }
+                        };
+                        Ok((key, Node::try_from(v)?))
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+            Value::Tagged(tagged) => Node::try_from(tagged.value)?,
+        })
+    }
+}
+
+/// Reads a YAML mapping into an env map, flattening nested mappings into `A__B` keys.
+/// ```rust
+/// # use dotenv_lib::{yaml_to_env_map, ArrayStyle};
+/// let env = yaml_to_env_map("db:\n  host: x\nhosts: [a, b]\n", ArrayStyle::Indexed).unwrap();
+/// assert_eq!(env["db__host"], "x");
+/// assert_eq!(env["hosts__1"], "b");
+/// ```
+#[cfg(feature = "yaml")]
+pub fn yaml_to_env_map(contents: &str, arrays: ArrayStyle) -> Result<EnvMap, ConvertError> {
+    let value: serde_yaml::Value =
+        serde_yaml::from_str(contents).map_err(|err| ConvertError::Parse {
+            format: "YAML",
+            message: err.to_string(),
+        })?;
+    Node::try_from(value)?.into_env_map("YAML", arrays)
+}
+
+/// Writes an env map as a flat YAML mapping of strings, with the keys in sorted order.
+#[cfg(feature = "yaml")]
+pub fn env_map_to_yaml(env: &EnvMap) -> Result<String, ConvertError> {
+    serde_yaml::to_string(&sorted(env)).map_err(|err| ConvertError::Serialize {
+        format: "YAML",
+        message: err.to_string(),
+    })
+}
+
+#[cfg(feature = "toml")]
+impl From<toml::Value> for Node {
+    fn from(value: toml::Value) -> Self {
+        use toml::Value;
+        match value {
+            Value::String(s) => Node::Scalar(s),
+            Value::Integer(i) => Node::Scalar(i.to_string()),
+            Value::Float(f) => Node::Scalar(f.to_string()),
+            Value::Boolean(b) => Node::Scalar(b.to_string()),
+            Value::Datetime(d) => Node::Scalar(d.to_string()),
+            Value::Array(items) => Node::Array(items.into_iter().map(Node::from).collect()),
+            Value::Table(members) => {
+                Node::Object(members.into_iter().map(|(k, v)| (k, v.into())).collect())
+            }
+        }
+    }
+}
+
+/// Reads a TOML document into an env map, flattening tables into `A__B` keys.
+/// ```rust
+/// # use dotenv_lib::{toml_to_env_map, ArrayStyle};
+/// let env = toml_to_env_map("port = 80\n[db]\nhost = \"x\"\n", ArrayStyle::Joined).unwrap();
+/// assert_eq!(env["port"], "80");
+/// assert_eq!(env["db__host"], "x");
+/// ```
+#[cfg(feature = "toml")]
+pub fn toml_to_env_map(contents: &str, arrays: ArrayStyle) -> Result<EnvMap, ConvertError> {
+    let value: toml::Table =
+        contents
+            .parse()
+            .map_err(|err: toml::de::Error| ConvertError::Parse {
+                format: "TOML",
+                message: err.to_string(),
+            })?;
+    Node::from(toml::Value::Table(value)).into_env_map("TOML", arrays)
+}
+
+/// Writes an env map as a flat TOML table of strings, with the keys in sorted order.
+#[cfg(feature = "toml")]
+pub fn env_map_to_toml(env: &EnvMap) -> Result<String, ConvertError> {
+    toml::to_string(&sorted(env)).map_err(|err| ConvertError::Serialize {
+        format: "TOML",
+        message: err.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ConvertError, EnvMap, env_map_to_env_string};
+
+    #[test]
+    fn env_string_is_sorted_and_quoted() {
+        let env: EnvMap = [("B", "two words"), ("A", "1")]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        assert_eq!(env_map_to_env_string(&env).unwrap(), "A=1\nB='two words'\n");
+    }
+
+    #[test]
+    fn env_string_rejects_invalid_keys() {
+        let env: EnvMap = [("db-host".to_string(), "x".to_string())]
+            .into_iter()
+            .collect();
+        assert!(matches!(
+            env_map_to_env_string(&env),
+            Err(ConvertError::Unrepresentable { key, .. }) if key == "db-host"
+        ));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_conversion() {
+        use crate::{ArrayStyle, env_map_to_json, json_to_env_map};
+
+        let json = r#"{"app": {"name": "x", "debug": true, "ratio": 0.5, "none": null},
+            "hosts": ["a", "b"], "servers": [{"port": 1}, {"port": 2}]}"#;
+        let env = json_to_env_map(json, ArrayStyle::Joined).unwrap();
+        assert_eq!(env["app__name"], "x");
+        assert_eq!(env["app__debug"], "true");
+        assert_eq!(env["app__ratio"], "0.5");
+        assert!(!env.contains_key("app__none"));
+        assert_eq!(env["hosts"], "a,b");
+        assert_eq!(env["servers__1__port"], "2");
+        let indexed = json_to_env_map(json, ArrayStyle::Indexed).unwrap();
+        assert_eq!(indexed["hosts__0"], "a");
+
+        assert!(matches!(
+            json_to_env_map("[1]", ArrayStyle::Joined),
+            Err(ConvertError::Parse { .. })
+        ));
+        assert!(matches!(
+            json_to_env_map(r#"{"a": ["x,y"]}"#, ArrayStyle::Joined),
+            Err(ConvertError::Unrepresentable { .. })
+        ));
+        assert!(matches!(
+            json_to_env_map(r#"{"db": {"host-name": "x"}}"#, ArrayStyle::Joined),
+            Err(ConvertError::Unrepresentable { key, .. }) if key == "db__host-name"
+        ));
+
+        let flat: EnvMap = [
+            ("B".to_string(), "2".to_string()),
+            ("A".to_string(), "1".to_string()),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            env_map_to_json(&flat).unwrap(),
+            "{\n  \"A\": \"1\",\n  \"B\": \"2\"\n}"
+        );
+        assert_eq!(
+            json_to_env_map(&env_map_to_json(&flat).unwrap(), ArrayStyle::Joined).unwrap(),
+            flat
+        );
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn yaml_conversion() {
+        use crate::{ArrayStyle, env_map_to_yaml, yaml_to_env_map};
+
+        let env = yaml_to_env_map("a:\n  b: 1\n  c: [x, y]\n  1: z\n", ArrayStyle::Joined).unwrap();
+        assert_eq!(env["a__b"], "1");
+        assert_eq!(env["a__c"], "x,y");
+        assert_eq!(env["a__1"], "z");
+        assert!(matches!(
+            yaml_to_env_map("? [a, b]\n: x\n", ArrayStyle::Joined),
+            Err(ConvertError::Parse { .. })
+        ));
+        let flat: EnvMap = [("K".to_string(), "multi\nline".to_string())]
+            .into_iter()
+            .collect();
+        assert_eq!(
+            yaml_to_env_map(&env_map_to_yaml(&flat).unwrap(), ArrayStyle::Joined).unwrap(),
+            flat
+        );
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml_conversion() {
+        use crate::{ArrayStyle, env_map_to_toml, toml_to_env_map};
+
+        let env = toml_to_env_map(
+            "name = \"x\"\n[[servers]]\nport = 1\n[[servers]]\nport = 2\n",
+            ArrayStyle::Joined,
+        )
+        .unwrap();
+        assert_eq!(env["name"], "x");
+        assert_eq!(env["servers__0__port"], "1");
+        let flat: EnvMap = [("A__B".to_string(), "it's".to_string())]
+            .into_iter()
+            .collect();
+        assert_eq!(env_map_to_toml(&flat).unwrap(), "A__B = \"it's\"\n");
+        assert_eq!(
+            toml_to_env_map(&env_map_to_toml(&flat).unwrap(), ArrayStyle::Joined).unwrap(),
+            flat
+        );
+    }
+}
@@ -0,0 +1,541 @@
+// serde deserialization of parsed `.env` files into typed config structs
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::de::value::{SeqDeserializer, StrDeserializer};
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, Visitor};
+
+use crate::typed::parse_bool;
+use crate::{EnvMap, NESTED_SEPARATOR, ParsedEnv, screaming_snake_case};
+
+#[derive(Debug, PartialEq)]
+pub enum DeError {
+    MissingKey {
+        key: String,
+    },
+    InvalidValue {
+        key: String,
+        value: String,
+        line: Option<u64>,
+        reason: String,
+    },
+    Message(String),
+    // a field the struct expected but could not find, before the prefix of its key is known
+    MissingField(&'static str),
+}
+
+impl fmt::Display for DeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeError::MissingKey { key } => write!(f, "Missing required key '{key}'"),
+            DeError::InvalidValue {
+                key,
+                value,
+                line: Some(line),
+                reason,
+            } => write!(
+                f,
+                "Invalid value '{value}' for key '{key}' on line {line}: {reason}"
+            ),
+            DeError::InvalidValue {
+                key,
+                value,
+                line: None,
+                reason,
+            } => write!(f, "Invalid value '{value}' for key '{key}': {reason}"),
+            DeError::Message(msg) => write!(f, "Deserialization error: {msg}"),
+            DeError::MissingField(field) => write!(f, "Missing required field '{field}'"),
+        }
+    }
+}
+
+impl std::error::Error for DeError {}
+
+impl de::Error for DeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        DeError::Message(msg.to_string())
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        DeError::MissingField(field)
+    }
+}
+
+struct EnvSource {
+    values: BTreeMap<String, (String, Option<u64>)>,
+}
+
+impl EnvSource {
+    fn has_nested(&self, key: &str) -> bool {
+        let nested_prefix = format!("{key}{NESTED_SEPARATOR}");
+        self.values
+            .range(nested_prefix.clone()..)
+            .next()
+            .is_some_and(|(k, _)| k.starts_with(&nested_prefix))
+    }
+}
+
+/// Deserializes a map of key-value pairs, such as one returned by [`process_dot_env`](crate::process_dot_env),
+/// into any type that implements [`Deserialize`](serde::Deserialize).
+///
+/// Struct fields are read from SCREAMING_SNAKE_CASE keys, nested structs from keys joined with `__`,
+/// and sequences from comma separated values.
+/// ```rust
+/// # use dotenv_lib::{from_env_map, process_dot_env};
+/// #[derive(serde::Deserialize)]
+/// struct Config {
+///     port: u16,
+///     debug: Option<bool>,
+///     hosts: Vec<String>,
+///     database: Database,
+/// }
+///
+/// #[derive(serde::Deserialize)]
+/// struct Database {
+///     url: String,
+/// }
+///
+/// let map = process_dot_env(
+///     "PORT=8080\nHOSTS=a,b\nDATABASE__URL=postgres://db\n".to_string(),
+/// ).unwrap();
+/// let config: Config = from_env_map(&map).unwrap();
+/// assert_eq!(config.port, 8080);
+/// assert_eq!(config.debug, None);
+/// assert_eq!(config.hosts, vec!["a", "b"]);
+/// assert_eq!(config.database.url, "postgres://db");
+/// ```
+pub fn from_env_map<T: DeserializeOwned>(map: &EnvMap) -> Result<T, DeError> {
+    from_env_map_with_prefix(map, "")
+}
+
+/// Like [`from_env_map`], but only reads keys that start with `prefix`, which is stripped before
+/// matching them to fields. For example, with the prefix `APP_` the field `port` is read from `APP_PORT`.
+pub fn from_env_map_with_prefix<T: DeserializeOwned>(
+    map: &EnvMap,
+    prefix: &str,
+) -> Result<T, DeError> {
+    let source = EnvSource {
+        values: map
+            .iter()
+            .map(|(k, v)| (k.clone(), (v.clone(), None)))
+            .collect(),
+    };
+    T::deserialize(ScopeDeserializer {
+        source: &source,
+        prefix: prefix.to_string(),
+    })
+}
+
+/// Like [`from_env_map`], but errors also name the line each invalid value came from.
+pub fn from_parsed_env<T: DeserializeOwned>(env: &ParsedEnv) -> Result<T, DeError> {
+    from_parsed_env_with_prefix(env, "")
+}
+
+/// Like [`from_env_map_with_prefix`], but errors also name the line each invalid value came from.
+pub fn from_parsed_env_with_prefix<T: DeserializeOwned>(
+    env: &ParsedEnv,
+    prefix: &str,
+) -> Result<T, DeError> {
+    let source = EnvSource {
+        values: env
+            .entries()
+            .iter()
+            .map(|e| (e.key.clone(), (e.value.clone(), Some(e.line))))
+            .collect(),
+    };
+    T::deserialize(ScopeDeserializer {
+        source: &source,
+        prefix: prefix.to_string(),
+    })
+}
+
+/// deserializes the keys under a prefix as a struct or map
+struct ScopeDeserializer<'a> {
+    source: &'a EnvSource,
+    prefix: String,
+}
+
+impl ScopeDeserializer<'_> {
+    // turn a field the derived code reported missing into the full key that was looked up
+    fn resolve_missing(&self, err: DeError) -> DeError {
+        match err {
+            DeError::MissingField(field) => DeError::MissingKey {
+                key: format!("{}{}", self.prefix, screaming_snake_case(field)),
+            },
+            other => other,
+        }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for ScopeDeserializer<'_> {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        let keys = self
+            .source
+            .values
+            .keys()
+            .filter(|k| k.starts_with(&self.prefix) && k.len() > self.prefix.len())
+            .map(|k| (k[self.prefix.len()..].to_string(), k.clone()))
+            .collect::<Vec<_>>()
+            .into_iter();
+        visitor.visit_map(ScopeMapAccess {
+            source: self.source,
+            pending: keys,
+            current: None,
+        })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        let pending = fields
+            .iter()
+            .map(|field| {
+                (
+                    field.to_string(),
+                    format!("{}{}", self.prefix, screaming_snake_case(field)),
+                )
+            })
+            .filter(|(_, key)| self.source.values.contains_key(key) || self.source.has_nested(key))
+            .collect::<Vec<_>>()
+            .into_iter();
+        visitor
+            .visit_map(ScopeMapAccess {
+                source: self.source,
+                pending,
+                current: None,
+            })
+            .map_err(|err| self.resolve_missing(err))
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct enum identifier ignored_any
+    }
+}
+
+/// walks the fields (or keys) of a scope, handing out a value or a nested scope for each
+struct ScopeMapAccess<'a> {
+    source: &'a EnvSource,
+    pending: std::vec::IntoIter<(String, String)>,
+    current: Option<String>,
+}
+
+impl<'de> MapAccess<'de> for ScopeMapAccess<'_> {
+    type Error = DeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, DeError> {
+        match self.pending.next() {
+            Some((field, key)) => {
+                self.current = Some(key);
+                let field: StrDeserializer<DeError> = field.as_str().into_deserializer();
+                seed.deserialize(field).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, DeError> {
+        let key = self
+            .current
+            .take()
+            .ok_or_else(|| DeError::Message("value requested before key".to_string()))?;
+        match self.source.values.get(&key) {
+            Some((value, line)) => seed
+                .deserialize(ValueDeserializer {
+                    value,
+                    key: &key,
+                    line: *line,
+                })
+                .map_err(|err| with_context(err, &key, value, *line)),
+            None => seed.deserialize(ScopeDeserializer {
+                source: self.source,
+                prefix: format!("{key}{NESTED_SEPARATOR}"),
+            }),
+        }
+    }
+}
+
+// attach the key, value and line to errors raised while reading a single value
+fn with_context(err: DeError, key: &str, value: &str, line: Option<u64>) -> DeError {
+    match err {
+        DeError::Message(reason) => DeError::InvalidValue {
+            key: key.to_string(),
+            value: value.to_string(),
+            line,
+            reason,
+        },
+        other => other,
+    }
+}
+
+/// deserializes a single string value, parsing it into the type the visitor asks for
+struct ValueDeserializer<'a> {
+    value: &'a str,
+    key: &'a str,
+    line: Option<u64>,
+}
+
+impl ValueDeserializer<'_> {
+    fn parse<T>(&self, expected: &str) -> Result<T, DeError>
+    where
+        T: std::str::FromStr,
+        T::Err: fmt::Display,
+    {
+        self.value
+            .trim()
+            .parse()
+            .map_err(|err: T::Err| DeError::Message(format!("expected {expected} ({err})")))
+    }
+}
+
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident : $ty:ty),* $(,)?) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+                visitor.$visit(self.parse::<$ty>(stringify!($ty))?)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer<'_> {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_str(self.value)
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        match parse_bool(self.value) {
+            Some(b) => visitor.visit_bool(b),
+            None => Err(DeError::Message(
+                "expected one of 1/0, true/false, yes/no, on/off".to_string(),
+            )),
+        }
+    }
+
+    deserialize_parsed! {
+        deserialize_i8 => visit_i8: i8,
+        deserialize_i16 => visit_i16: i16,
+        deserialize_i32 => visit_i32: i32,
+        deserialize_i64 => visit_i64: i64,
+        deserialize_i128 => visit_i128: i128,
+        deserialize_u8 => visit_u8: u8,
+        deserialize_u16 => visit_u16: u16,
+        deserialize_u32 => visit_u32: u32,
+        deserialize_u64 => visit_u64: u64,
+        deserialize_u128 => visit_u128: u128,
+        deserialize_f32 => visit_f32: f32,
+        deserialize_f64 => visit_f64: f64,
+        deserialize_char => visit_char: char,
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        if self.value.is_empty() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        let items = self
+            .value
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(|item| ValueDeserializer {
+                value: item,
+                key: self.key,
+                line: self.line,
+            });
+        SeqDeserializer::new(items).deserialize_any(visitor)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        visitor.visit_enum(self.value.trim().into_deserializer())
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_unit()
+    }
+
+    serde::forward_to_deserialize_any! {
+        str string bytes byte_buf unit_struct map struct identifier ignored_any
+    }
+}
+
+impl<'de, 'a> IntoDeserializer<'de, DeError> for ValueDeserializer<'a> {
+    type Deserializer = ValueDeserializer<'a>;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use crate::{DeError, from_env_map, from_env_map_with_prefix, from_parsed_env, parse_env};
+    use crate::{EnvMap, process_dot_env};
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename_all = "lowercase")]
+    enum Level {
+        Debug,
+        Info,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Database {
+        url: String,
+        pool_size: Option<u32>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Config {
+        port: u16,
+        debug: bool,
+        log_level: Level,
+        hosts: Vec<String>,
+        timeout: Option<u64>,
+        database: Database,
+    }
+
+    fn map(contents: &str) -> EnvMap {
+        process_dot_env(contents.to_string()).expect("error processing env file")
+    }
+
+    #[test]
+    fn deserialize_nested_config() {
+        let env = map(
+            "PORT=8080\nDEBUG=yes\nLOG_LEVEL=info\nHOSTS='a, b,c'\nDATABASE__URL=postgres://db\nDATABASE__POOL_SIZE=4\n",
+        );
+        let config: Config = from_env_map(&env).unwrap();
+        assert_eq!(
+            config,
+            Config {
+                port: 8080,
+                debug: true,
+                log_level: Level::Info,
+                hosts: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+                timeout: None,
+                database: Database {
+                    url: "postgres://db".to_string(),
+                    pool_size: Some(4),
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn deserialize_with_prefix() {
+        let env = map("APP_URL=x\nAPP_POOL_SIZE=2\nURL=ignored\n");
+        let db: Database = from_env_map_with_prefix(&env, "APP_").unwrap();
+        assert_eq!(db.url, "x");
+        assert_eq!(db.pool_size, Some(2));
+    }
+
+    #[test]
+    fn missing_key_names_full_key() {
+        let env = map("PORT=1\nDEBUG=0\nLOG_LEVEL=debug\nHOSTS=a\nDATABASE__POOL_SIZE=1\n");
+        let err = from_env_map::<Config>(&env).unwrap_err();
+        assert_eq!(
+            err,
+            DeError::MissingKey {
+                key: "DATABASE__URL".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_value_names_key_and_line() {
+        let env = parse_env("URL=x\nPOOL_SIZE=many\n".to_string()).unwrap();
+        match from_parsed_env::<Database>(&env) {
+            Err(DeError::InvalidValue {
+                key, value, line, ..
+            }) => {
+                assert_eq!(key, "POOL_SIZE");
+                assert_eq!(value, "many");
+                assert_eq!(line, Some(2));
+            }
+            other => panic!("Did not return correct error: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn unknown_enum_variant_err() {
+        let env = map("PORT=1\nDEBUG=0\nLOG_LEVEL=loud\nHOSTS=a\nDATABASE__URL=x\n");
+        match from_env_map::<Config>(&env) {
+            Err(DeError::InvalidValue { key, .. }) => assert_eq!(key, "LOG_LEVEL"),
+            other => panic!("Did not return correct error: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn camel_case_fields_use_screaming_snake_keys() {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Camel {
+            max_connections: u8,
+        }
+        let env = map("MAX_CONNECTIONS=9\n");
+        let camel: Camel = from_env_map(&env).unwrap();
+        assert_eq!(camel.max_connections, 9);
+    }
+}
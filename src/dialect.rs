@@ -0,0 +1,781 @@
+// parsing env files with the rules of other dotenv implementations, so that a file can be read
+// exactly as the program consuming it will read it
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use crate::{Annotations, EnvEntry, EnvError, ParsedEnv};
+
+/// Where `#` starts a comment after an unquoted value. Lines starting with `#` are always comments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InlineComments {
+    /// `#` is part of the value
+    Never,
+    /// `#` starts a comment when a space or tab comes before it, so `a#b` is kept whole
+    AfterWhitespace,
+    /// `#` always ends the value
+    Anywhere,
+}
+
+/// What an escape sequence that a dialect does not know, such as `\q`, becomes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnknownEscape {
+    /// the backslash and the character are both kept
+    Keep,
+    /// only the character is kept
+    DropBackslash,
+    /// the value is an error
+    Error,
+}
+
+/// The backslash escapes a dialect decodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Escapes {
+    /// each escaped character and what it becomes, such as `('n', '\n')`
+    pub sequences: &'static [(char, char)],
+    pub unknown: UnknownEscape,
+}
+
+/// What a quote mark at the start of a value does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quotes {
+    /// the quote mark is an ordinary character
+    Literal,
+    /// everything up to the closing quote is kept as written, including line breaks
+    Raw,
+    /// like `Raw`, but backslash escapes are decoded and an escaped quote does not close the value
+    Escaped(Escapes),
+}
+
+/// Which variable references are replaced in unquoted and double quoted values. References are
+/// resolved against the values set earlier in the file and the host environment, in the dialect's
+/// [`LookupOrder`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interpolation {
+    /// `$` is an ordinary character
+    None,
+    /// `${VAR}`, and `${VAR:-default}` in dialects whose [`InterpolationDefaults`] allow it
+    Braced,
+    /// `$VAR` as well as the braced forms
+    Full,
+    /// the same as `Full`, with `$$` written for a literal `$`
+    FullWithDollarEscape,
+}
+
+/// Where a variable reference is looked up first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LookupOrder {
+    /// the values set earlier in the file, then the host environment
+    FileFirst,
+    /// the host environment, then the values set earlier in the file
+    HostFirst,
+}
+
+/// When the default in `${VAR:-default}` is used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterpolationDefaults {
+    /// defaults are not supported, so the whole text between the braces is looked up as a name,
+    /// which is never set
+    None,
+    /// the default is used only if `VAR` is unset, as python-dotenv does
+    Unset,
+    /// the default is used if `VAR` is unset or empty, as a shell and Docker Compose do
+    UnsetOrEmpty,
+}
+
+/// What a line holding only a key, such as `DEBUG`, does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BareKeys {
+    /// the line is an error
+    Error,
+    /// the line is ignored
+    Skip,
+    /// the key is set to an empty value
+    Empty,
+    /// the key takes its value from the host environment, and is skipped if it is not set there
+    Inherit,
+}
+
+const NEWLINE_ESCAPES: &[(char, char)] = &[('n', '\n'), ('r', '\r')];
+const C_ESCAPES: &[(char, char)] = &[
+    ('a', '\u{7}'),
+    ('b', '\u{8}'),
+    ('f', '\u{c}'),
+    ('n', '\n'),
+    ('r', '\r'),
+    ('t', '\t'),
+    ('v', '\u{b}'),
+    ('\\', '\\'),
+    ('"', '"'),
+    ('\'', '\''),
+];
+
+/// no escapes are decoded, but an escaped quote does not close the value
+const KEEP_ESCAPES: Escapes = Escapes {
+    sequences: &[],
+    unknown: UnknownEscape::Keep,
+};
+
+const DOTENVY_ESCAPES: Escapes = Escapes {
+    sequences: &[
+        ('\\', '\\'),
+        ('\'', '\''),
+        ('"', '"'),
+        ('$', '$'),
+        (' ', ' '),
+        ('n', '\n'),
+    ],
+    unknown: UnknownEscape::Error,
+};
+
+const COMPOSE_ESCAPES: &[(char, char)] = &[
+    ('a', '\u{7}'),
+    ('b', '\u{8}'),
+    ('f', '\u{c}'),
+    ('n', '\n'),
+    ('r', '\r'),
+    ('t', '\t'),
+    ('v', '\u{b}'),
+    ('\\', '\\'),
+    ('"', '"'),
+    ('$', '$'),
+];
+
+/// The rules an implementation of `.env` files follows. The presets mirror popular
+/// implementations, and a custom dialect can be built by changing their fields.
+/// ```rust
+/// # use dotenv_lib::{Dialect, InlineComments};
+/// let contents = "COLOR=#fff # white\n";
+/// let node = Dialect::NODE.parse(contents, |_| None).unwrap();
+/// assert_eq!(node.get_str("COLOR"), Some(""));
+/// let python = Dialect::PYTHON.parse(contents, |_| None).unwrap();
+/// assert_eq!(python.get_str("COLOR"), Some("#fff"));
+///
+/// let strict = Dialect {
+///     inline_comments: InlineComments::Never,
+///     ..Dialect::PYTHON
+/// };
+/// assert_eq!(strict.parse(contents, |_| None).unwrap().get_str("COLOR"), Some("#fff # white"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dialect {
+    /// the short name used to pick the dialect, such as `node`
+    pub name: &'static str,
+    /// whether keys may be written as `export KEY=value`
+    pub export_prefix: bool,
+    pub inline_comments: InlineComments,
+    pub single_quotes: Quotes,
+    pub double_quotes: Quotes,
+    pub backticks: Quotes,
+    /// the escapes decoded in unquoted values, if any
+    pub unquoted_escapes: Option<Escapes>,
+    pub interpolation: Interpolation,
+    pub interpolation_defaults: InterpolationDefaults,
+    pub lookup_order: LookupOrder,
+    /// whether `KEY=` sets an empty value rather than being an error
+    pub empty_values: bool,
+    pub bare_keys: BareKeys,
+    /// whether lines that cannot be parsed are ignored rather than being an error
+    pub skip_invalid_lines: bool,
+}
+
+impl Dialect {
+    /// The `dotenv` package for Node.js: `#` always starts a comment in unquoted values, double
+    /// quoted values decode `\n` and `\r`, and nothing is interpolated. An escaped quote does not
+    /// close a value in any quote style, and keeps its backslash.
+    pub const NODE: Dialect = Dialect {
+        name: "node",
+        export_prefix: true,
+        inline_comments: InlineComments::Anywhere,
+        single_quotes: Quotes::Escaped(KEEP_ESCAPES),
+        double_quotes: Quotes::Escaped(Escapes {
+            sequences: NEWLINE_ESCAPES,
+            unknown: UnknownEscape::Keep,
+        }),
+        backticks: Quotes::Escaped(KEEP_ESCAPES),
+        unquoted_escapes: None,
+        interpolation: Interpolation::None,
+        interpolation_defaults: InterpolationDefaults::None,
+        lookup_order: LookupOrder::FileFirst,
+        empty_values: true,
+        bare_keys: BareKeys::Skip,
+        skip_invalid_lines: true,
+    };
+
+    /// `python-dotenv`: comments need whitespace before them, both quote styles decode escapes,
+    /// and `${VAR}` is interpolated.
+    pub const PYTHON: Dialect = Dialect {
+        name: "python",
+        export_prefix: true,
+        inline_comments: InlineComments::AfterWhitespace,
+        single_quotes: Quotes::Escaped(Escapes {
+            sequences: &[('\\', '\\'), ('\'', '\'')],
+            unknown: UnknownEscape::Keep,
+        }),
+        double_quotes: Quotes::Escaped(Escapes {
+            sequences: C_ESCAPES,
+            unknown: UnknownEscape::Keep,
+        }),
+        backticks: Quotes::Literal,
+        unquoted_escapes: None,
+        interpolation: Interpolation::Braced,
+        interpolation_defaults: InterpolationDefaults::Unset,
+        lookup_order: LookupOrder::FileFirst,
+        empty_values: true,
+        bare_keys: BareKeys::Skip,
+        skip_invalid_lines: true,
+    };
+
+    /// The `dotenv` gem for Ruby: `#` always starts a comment in unquoted values, double quoted
+    /// values decode `\n` and `\r` and drop other backslashes, and `$VAR` is interpolated without
+    /// `${VAR:-default}`.
+    pub const RUBY: Dialect = Dialect {
+        name: "ruby",
+        export_prefix: true,
+        inline_comments: InlineComments::Anywhere,
+        single_quotes: Quotes::Raw,
+        double_quotes: Quotes::Escaped(Escapes {
+            sequences: &[('n', '\n'), ('r', '\r'), ('$', '$')],
+            unknown: UnknownEscape::DropBackslash,
+        }),
+        backticks: Quotes::Literal,
+        unquoted_escapes: None,
+        interpolation: Interpolation::Full,
+        interpolation_defaults: InterpolationDefaults::None,
+        lookup_order: LookupOrder::FileFirst,
+        empty_values: true,
+        bare_keys: BareKeys::Empty,
+        skip_invalid_lines: true,
+    };
+
+    /// The `dotenvy` crate for Rust: comments need whitespace before them, unquoted and double
+    /// quoted values decode a few escapes and reject others, and `$VAR` is interpolated from the
+    /// host environment first, without `${VAR:-default}`.
+    pub const DOTENVY: Dialect = Dialect {
+        name: "dotenvy",
+        export_prefix: true,
+        inline_comments: InlineComments::AfterWhitespace,
+        single_quotes: Quotes::Raw,
+        double_quotes: Quotes::Escaped(DOTENVY_ESCAPES),
+        backticks: Quotes::Literal,
+        unquoted_escapes: Some(DOTENVY_ESCAPES),
+        interpolation: Interpolation::Full,
+        interpolation_defaults: InterpolationDefaults::None,
+        lookup_order: LookupOrder::HostFirst,
+        empty_values: true,
+        bare_keys: BareKeys::Error,
+        skip_invalid_lines: false,
+    };
+
+    /// The `env_file` and `.env` files of Docker Compose: comments need whitespace before them,
+    /// double quoted values decode C escapes, `$VAR` is interpolated with `$$` for a literal `$`,
+    /// and a bare key takes its value from the host.
+    pub const DOCKER_COMPOSE: Dialect = Dialect {
+        name: "compose",
+        export_prefix: true,
+        inline_comments: InlineComments::AfterWhitespace,
+        single_quotes: Quotes::Raw,
+        double_quotes: Quotes::Escaped(Escapes {
+            sequences: COMPOSE_ESCAPES,
+            unknown: UnknownEscape::Keep,
+        }),
+        backticks: Quotes::Literal,
+        unquoted_escapes: None,
+        interpolation: Interpolation::FullWithDollarEscape,
+        interpolation_defaults: InterpolationDefaults::UnsetOrEmpty,
+        lookup_order: LookupOrder::FileFirst,
+        empty_values: true,
+        bare_keys: BareKeys::Inherit,
+        skip_invalid_lines: false,
+    };
+
+    pub const PRESETS: [Dialect; 5] = [
+        Dialect::NODE,
+        Dialect::PYTHON,
+        Dialect::RUBY,
+        Dialect::DOTENVY,
+        Dialect::DOCKER_COMPOSE,
+    ];
+
+    /// Parses `contents` with the rules of this dialect. `host` looks up variables in the
+    /// environment the file is loaded into, for interpolation and bare keys; pass `|_| None` to
+    /// read the file on its own. When a key is set more than once the last value wins.
+    pub fn parse(
+        &self,
+        contents: &str,
+        host: impl Fn(&str) -> Option<String>,
+    ) -> Result<ParsedEnv, EnvError> {
+        let contents = contents.strip_prefix('\u{feff}').unwrap_or(contents);
+        let chars: Vec<char> = contents.chars().collect();
+        let mut cursor = Cursor {
+            chars: &chars,
+            pos: 0,
+            line: 1,
+            line_start: 0,
+        };
+        let mut entries = Vec::new();
+        let mut values = HashMap::new();
+        while cursor.peek().is_some() {
+            let start = cursor.clone();
+            let lookup = |name: &str| match self.lookup_order {
+                LookupOrder::FileFirst => values.get(name).cloned().or_else(|| host(name)),
+                LookupOrder::HostFirst => host(name).or_else(|| values.get(name).cloned()),
+            };
+            match self.parse_line(&mut cursor, &lookup) {
+                Ok(Some((key, value))) => {
+                    values.insert(key.clone(), value.clone());
+                    entries.push(EnvEntry {
+                        key,
+                        value,
+                        line: start.line,
+                        annotations: Annotations::default(),
+                    });
+                }
+                Ok(None) => {}
+                Err(_) if self.skip_invalid_lines => {
+                    cursor = start;
+                    cursor.skip_line();
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(ParsedEnv::from_entries(entries))
+    }
+
+    fn quotes(&self, quote: char) -> Quotes {
+        match quote {
+            '\'' => self.single_quotes,
+            '"' => self.double_quotes,
+            '`' => self.backticks,
+            _ => Quotes::Literal,
+        }
+    }
+
+    /// parses one line, or several for a quoted value with line breaks, returning the assignment
+    /// on it if there is one
+    fn parse_line(
+        &self,
+        cursor: &mut Cursor,
+        lookup: &dyn Fn(&str) -> Option<String>,
+    ) -> Result<Option<(String, String)>, EnvError> {
+        cursor.skip_blanks();
+        if cursor.at_line_end() || cursor.peek() == Some('#') {
+            cursor.skip_line();
+            return Ok(None);
+        }
+        if self.export_prefix && cursor.starts_with("export") {
+            let after = cursor.chars.get(cursor.pos + 6);
+            if matches!(after, Some(' ' | '\t')) {
+                cursor.pos += 6;
+                cursor.skip_blanks();
+            }
+        }
+        let mut key = String::new();
+        while let Some(c) = cursor
+            .peek()
+            .filter(|c| !matches!(c, '=' | ' ' | '\t' | '\n'))
+        {
+            key.push(c);
+            cursor.bump();
+        }
+        let key = key.trim_end_matches('\r').to_string();
+        let line = cursor.line;
+        if key.is_empty() {
+            return Err(EnvError::MissingKey { line });
+        }
+        cursor.skip_blanks();
+        if cursor.at_line_end() || cursor.peek() == Some('#') {
+            cursor.skip_line();
+            return match self.bare_keys {
+                BareKeys::Error => Err(EnvError::FoundOnlyKey { line }),
+                BareKeys::Skip => Ok(None),
+                BareKeys::Empty => Ok(Some((key, String::new()))),
+                BareKeys::Inherit => Ok(lookup(&key).map(|value| (key, value))),
+            };
+        }
+        if cursor.peek() != Some('=') {
+            return Err(EnvError::UnexpectedToken {
+                expected: "=".to_string(),
+                found: cursor.peek().map(String::from).unwrap_or_default(),
+                line,
+                character: cursor.character(),
+            });
+        }
+        cursor.bump();
+        cursor.skip_blanks();
+        let value = match cursor.peek() {
+            Some(quote @ ('\'' | '"' | '`')) if self.quotes(quote) != Quotes::Literal => {
+                self.quoted_value(cursor, quote, lookup)?
+            }
+            _ => self.unquoted_value(cursor, lookup)?,
+        };
+        if value.is_empty() && !self.empty_values {
+            return Err(EnvError::MissingValue { line });
+        }
+        Ok(Some((key, value)))
+    }
+
+    fn quoted_value(
+        &self,
+        cursor: &mut Cursor,
+        quote: char,
+        lookup: &dyn Fn(&str) -> Option<String>,
+    ) -> Result<String, EnvError> {
+        let (line, character) = (cursor.line, cursor.character());
+        let rules = self.quotes(quote);
+        cursor.bump();
+        let mut raw = String::new();
+        loop {
+            match cursor.bump() {
+                None => return Err(EnvError::UnclosedValue { line }),
+                Some(c) if c == quote => break,
+                Some('\\') if matches!(rules, Quotes::Escaped(_)) => {
+                    raw.push('\\');
+                    raw.extend(cursor.bump());
+                }
+                Some(c) => raw.push(c),
+            }
+        }
+        cursor.skip_blanks();
+        if !(cursor.at_line_end() || cursor.peek() == Some('#')) {
+            return Err(EnvError::UnexpectedToken {
+                expected: "comment or new line".to_string(),
+                found: cursor.peek().map(String::from).unwrap_or_default(),
+                line: cursor.line,
+                character: cursor.character(),
+            });
+        }
+        cursor.skip_line();
+        let escapes = match rules {
+            Quotes::Escaped(escapes) => Some(escapes),
+            _ => None,
+        };
+        self.decode(&raw, escapes, quote == '"', lookup, (line, character))
+    }
+
+    fn unquoted_value(
+        &self,
+        cursor: &mut Cursor,
+        lookup: &dyn Fn(&str) -> Option<String>,
+    ) -> Result<String, EnvError> {
+        let (line, character) = (cursor.line, cursor.character());
+        let mut raw = String::new();
+        while let Some(c) = cursor.peek().filter(|_| !cursor.at_line_end()) {
+            let comment = c == '#'
+                && match self.inline_comments {
+                    InlineComments::Never => false,
+                    InlineComments::AfterWhitespace => raw.ends_with([' ', '\t']),
+                    InlineComments::Anywhere => true,
+                };
+            if comment {
+                break;
+            }
+            raw.push(c);
+            cursor.bump();
+        }
+        cursor.skip_line();
+        self.decode(
+            raw.trim(),
+            self.unquoted_escapes,
+            true,
+            lookup,
+            (line, character),
+        )
+    }
+
+    /// decodes escapes and replaces variable references in the text of a value. `position` is
+    /// where the value starts, for errors
+    fn decode(
+        &self,
+        raw: &str,
+        escapes: Option<Escapes>,
+        interpolate: bool,
+        lookup: &dyn Fn(&str) -> Option<String>,
+        (line, character): (u64, u64),
+    ) -> Result<String, EnvError> {
+        let chars: Vec<char> = raw.chars().collect();
+        let mut value = String::with_capacity(raw.len());
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            i += 1;
+            match (c, escapes) {
+                ('\\', Some(escapes)) if i < chars.len() => {
+                    let escaped = chars[i];
+                    i += 1;
+                    match escapes.sequences.iter().find(|(e, _)| *e == escaped) {
+                        Some((_, decoded)) => value.push(*decoded),
+                        None => match escapes.unknown {
+                            UnknownEscape::Keep => value.extend(['\\', escaped]),
+                            UnknownEscape::DropBackslash => value.push(escaped),
+                            UnknownEscape::Error => {
+                                return Err(EnvError::UnexpectedToken {
+                                    expected: "a known escape sequence".to_string(),
+                                    found: format!("\\{escaped}"),
+                                    line,
+                                    character,
+                                });
+                            }
+                        },
+                    }
+                }
+                ('$', _) if interpolate && self.interpolation != Interpolation::None => {
+                    i += self.interpolate(&chars[i..], lookup, &mut value);
+                }
+                (c, _) => value.push(c),
+            }
+        }
+        Ok(value)
+    }
+
+    /// replaces the variable reference after a `$`, returning how many characters it used
+    fn interpolate(
+        &self,
+        rest: &[char],
+        lookup: &dyn Fn(&str) -> Option<String>,
+        value: &mut String,
+    ) -> usize {
+        let is_name = |c: &char| c.is_ascii_alphanumeric() || *c == '_';
+        match rest.first() {
+            Some('$') if self.interpolation == Interpolation::FullWithDollarEscape => {
+                value.push('$');
+                1
+            }
+            Some('{') => match rest.iter().position(|c| *c == '}') {
+                Some(end) => {
+                    let reference: String = rest[1..end].iter().collect();
+                    let (name, default) = match reference.split_once(":-") {
+                        Some((name, default))
+                            if self.interpolation_defaults != InterpolationDefaults::None =>
+                        {
+                            (name, Some(default))
+                        }
+                        _ => (reference.as_str(), None),
+                    };
+                    let resolved = lookup(name).filter(|v| {
+                        self.interpolation_defaults != InterpolationDefaults::UnsetOrEmpty
+                            || default.is_none()
+                            || !v.is_empty()
+                    });
+                    value.push_str(&resolved.unwrap_or_else(|| default.unwrap_or("").to_string()));
+                    end + 1
+                }
+                None => {
+                    value.push('$');
+                    0
+                }
+            },
+            Some(c) if self.interpolation != Interpolation::Braced && is_name(c) => {
+                let name: String = rest.iter().take_while(|c| is_name(c)).collect();
+                value.push_str(&lookup(&name).unwrap_or_default());
+                name.len()
+            }
+            _ => {
+                value.push('$');
+                0
+            }
+        }
+    }
+}
+
+impl FromStr for Dialect {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Dialect::PRESETS
+            .into_iter()
+            .find(|dialect| dialect.name == s)
+            .ok_or_else(|| {
+                format!("unknown dialect '{s}', expected node, python, ruby, dotenvy or compose")
+            })
+    }
+}
+
+/// a position in the characters of a file
+#[derive(Clone)]
+struct Cursor<'a> {
+    chars: &'a [char],
+    pos: usize,
+    line: u64,
+    line_start: usize,
+}
+
+impl Cursor<'_> {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.line_start = self.pos;
+        }
+        Some(c)
+    }
+
+    /// the 1-based character on the current line
+    fn character(&self) -> u64 {
+        (self.pos - self.line_start + 1) as u64
+    }
+
+    fn starts_with(&self, text: &str) -> bool {
+        text.chars()
+            .enumerate()
+            .all(|(i, c)| self.chars.get(self.pos + i) == Some(&c))
+    }
+
+    /// whether the rest of the line is empty, treating `\r\n` as a line break
+    fn at_line_end(&self) -> bool {
+        match self.peek() {
+            None | Some('\n') => true,
+            Some('\r') => matches!(self.chars.get(self.pos + 1), None | Some('\n')),
+            _ => false,
+        }
+    }
+
+    fn skip_blanks(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t')) {
+            self.bump();
+        }
+    }
+
+    /// moves to the start of the next line
+    fn skip_line(&mut self) {
+        while let Some(c) = self.bump() {
+            if c == '\n' {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Dialect, EnvError, ParsedEnv};
+
+    fn values(env: &ParsedEnv) -> Vec<(&str, &str)> {
+        env.entries()
+            .iter()
+            .map(|e| (e.key.as_str(), e.value.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn presets_disagree() {
+        let contents =
+            "export A=x#y # comment\nB=\"one\\ttwo\\nthree\"\nC='$A \\n'\nD=${A}-$A\nE=\nF\n";
+        let read = |dialect: Dialect| dialect.parse(contents, |_| None).unwrap();
+        assert_eq!(
+            values(&read(Dialect::NODE)),
+            [
+                ("A", "x"),
+                ("B", "one\\ttwo\nthree"),
+                ("C", "$A \\n"),
+                ("D", "${A}-$A"),
+                ("E", "")
+            ]
+        );
+        assert_eq!(
+            values(&read(Dialect::PYTHON)),
+            [
+                ("A", "x#y"),
+                ("B", "one\ttwo\nthree"),
+                ("C", "$A \\n"),
+                ("D", "x#y-$A"),
+                ("E", "")
+            ]
+        );
+        assert_eq!(
+            values(&read(Dialect::RUBY)),
+            [
+                ("A", "x"),
+                ("B", "onettwo\nthree"),
+                ("C", "$A \\n"),
+                ("D", "x-x"),
+                ("E", ""),
+                ("F", "")
+            ]
+        );
+        assert_eq!(
+            Dialect::DOTENVY.parse(contents, |_| None),
+            Err(EnvError::UnexpectedToken {
+                expected: "a known escape sequence".to_string(),
+                found: "\\t".to_string(),
+                line: 2,
+                character: 3,
+            })
+        );
+        let compose = Dialect::DOCKER_COMPOSE
+            .parse(contents, |key| (key == "F").then(|| "host".to_string()))
+            .unwrap();
+        assert_eq!(
+            values(&compose),
+            [
+                ("A", "x#y"),
+                ("B", "one\ttwo\nthree"),
+                ("C", "$A \\n"),
+                ("D", "x#y-x#y"),
+                ("E", ""),
+                ("F", "host")
+            ]
+        );
+        assert_eq!("compose".parse(), Ok(Dialect::DOCKER_COMPOSE));
+        assert!("php".parse::<Dialect>().is_err());
+    }
+
+    #[test]
+    fn interpolation_and_invalid_lines() {
+        let contents =
+            "HOST=db\r\nURL=\"pg://${HOST}/${NAME:-app}\"\nPRICE=$$5\nnot a line\nMULTI='a\nb'\n";
+        let compose = Dialect::DOCKER_COMPOSE.parse(contents, |_| None);
+        assert_eq!(compose.unwrap_err().line(), 4);
+        let dotenvy = Dialect::DOTENVY
+            .parse("A=1\nB=\"\\$A $A\"\n", |_| None)
+            .unwrap();
+        assert_eq!(dotenvy.get_str("B"), Some("$A 1"));
+
+        let python = Dialect::PYTHON
+            .parse(contents, |key| {
+                (key == "HOST").then(|| "ignored".to_string())
+            })
+            .unwrap();
+        assert_eq!(
+            values(&python),
+            [
+                ("HOST", "db"),
+                ("URL", "pg://db/app"),
+                ("PRICE", "$$5"),
+                ("MULTI", "a\nb")
+            ]
+        );
+        assert_eq!(python.line("MULTI"), Some(5));
+        let unclosed = Dialect::DOTENVY.parse("A=\"open\n", |_| None);
+        assert_eq!(unclosed, Err(EnvError::UnclosedValue { line: 1 }));
+    }
+
+    #[test]
+    fn lookup_order_and_defaults() {
+        let contents = "HOST=file\nURL=$HOST/${PORT:-80}\n";
+        let host = |key: &str| (key == "HOST").then(|| "host".to_string());
+        let dotenvy = Dialect::DOTENVY.parse(contents, host).unwrap();
+        assert_eq!(dotenvy.get_str("URL"), Some("host/"));
+        let compose = Dialect::DOCKER_COMPOSE.parse(contents, host).unwrap();
+        assert_eq!(compose.get_str("URL"), Some("file/80"));
+
+        // python-dotenv only falls back when the variable is unset, Compose also when it is empty
+        let empty = "A=\nB=${A:-x}\n";
+        let python = Dialect::PYTHON.parse(empty, |_| None).unwrap();
+        assert_eq!(python.get_str("B"), Some(""));
+        let compose = Dialect::DOCKER_COMPOSE.parse(empty, |_| None).unwrap();
+        assert_eq!(compose.get_str("B"), Some("x"));
+    }
+
+    #[test]
+    fn node_escaped_single_quote() {
+        let node = Dialect::NODE
+            .parse("A='it\\'s'\nB=`say \\`hi\\``\n", |_| None)
+            .unwrap();
+        assert_eq!(values(&node), [("A", "it\\'s"), ("B", "say \\`hi\\`")]);
+    }
+}
@@ -0,0 +1,288 @@
+// exporting variables for Docker: `docker run` arguments, `--env-file` files, Dockerfile `ENV`
+// instructions and the `environment:` block of a compose service, and reading `--env-file` files
+// the way Docker does
+use crate::json::json_string;
+use crate::shell::is_variable_name;
+use crate::{Annotations, ConvertError, EnvEntry, EnvError, ParsedEnv};
+
+/// checks every key before anything is written, so each exporter only deals with values
+fn checked<'a>(
+    vars: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> Result<Vec<(&'a str, &'a str)>, ConvertError> {
+    vars.into_iter()
+        .map(|(key, value)| {
+            if is_variable_name(key) {
+                Ok((key, value))
+            } else {
+                Err(ConvertError::Unrepresentable {
+                    key: key.to_string(),
+                    reason: "not a valid variable name".to_string(),
+                })
+            }
+        })
+        .collect()
+}
+
+/// Returns `docker run` arguments setting each variable, as `-e` and `K=v` pairs. They are meant
+/// to be passed to the program directly rather than through a shell, so values are not quoted.
+/// ```rust
+/// # use dotenv_lib::docker_run_args;
+/// let args = docker_run_args([("GREETING", "hello world")]).unwrap();
+/// assert_eq!(args, ["-e", "GREETING=hello world"]);
+/// ```
+pub fn docker_run_args<'a>(
+    vars: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> Result<Vec<String>, ConvertError> {
+    Ok(checked(vars)?
+        .into_iter()
+        .flat_map(|(key, value)| ["-e".to_string(), format!("{key}={value}")])
+        .collect())
+}
+
+/// Writes a file for `docker run --env-file`. Docker takes everything after the first `=` as the
+/// value, quotes included, so values are written without quotes, and values holding a line break
+/// cannot be written at all.
+/// ```rust
+/// # use dotenv_lib::{docker_env_file, parse_env};
+/// let env = parse_env("GREETING='hello # world'\n".to_string()).unwrap();
+/// let vars = env.entries().iter().map(|e| (e.key.as_str(), e.value.as_str()));
+/// assert_eq!(docker_env_file(vars).unwrap(), "GREETING=hello # world\n");
+/// ```
+pub fn docker_env_file<'a>(
+    vars: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> Result<String, ConvertError> {
+    let mut file = String::new();
+    for (key, value) in checked(vars)? {
+        if value.contains(['\n', '\r']) {
+            return Err(ConvertError::Unrepresentable {
+                key: key.to_string(),
+                reason: "Docker env files cannot hold line breaks".to_string(),
+            });
+        }
+        file.push_str(&format!("{key}={value}\n"));
+    }
+    Ok(file)
+}
+
+/// Writes a Dockerfile `ENV` instruction setting every variable, one per line. Values are double
+/// quoted with `\`, `"` and `$` escaped, so Docker neither splits nor substitutes them. A
+/// Dockerfile cannot hold a line break inside a value, so such values are an error.
+/// ```rust
+/// # use dotenv_lib::dockerfile_env;
+/// let instruction = dockerfile_env([("HOME_DIR", "$HOME"), ("PORT", "8080")]).unwrap();
+/// assert_eq!(instruction, "ENV HOME_DIR=\"\\$HOME\" \\\n    PORT=\"8080\"\n");
+/// ```
+pub fn dockerfile_env<'a>(
+    vars: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> Result<String, ConvertError> {
+    let mut pairs = Vec::new();
+    for (key, value) in checked(vars)? {
+        if value.contains(['\n', '\r']) {
+            return Err(ConvertError::Unrepresentable {
+                key: key.to_string(),
+                reason: "Dockerfile ENV values cannot hold line breaks".to_string(),
+            });
+        }
+        let mut quoted = String::with_capacity(value.len() + 2);
+        for c in value.chars() {
+            if matches!(c, '\\' | '"' | '$') {
+                quoted.push('\\');
+            }
+            quoted.push(c);
+        }
+        pairs.push(format!("{key}=\"{quoted}\""));
+    }
+    if pairs.is_empty() {
+        return Ok(String::new());
+    }
+    Ok(format!("ENV {}\n", pairs.join(" \\\n    ")))
+}
+
+/// Writes an `environment:` mapping for a service in a compose file, indented to sit under the
+/// service. Values are YAML double quoted strings with `$` doubled, since compose would otherwise
+/// substitute variables in them.
+/// ```rust
+/// # use dotenv_lib::compose_environment;
+/// let block = compose_environment([("PRICE", "$5"), ("DEBUG", "true")]).unwrap();
+/// assert_eq!(block, "    environment:\n      \"PRICE\": \"$$5\"\n      \"DEBUG\": \"true\"\n");
+/// ```
+pub fn compose_environment<'a>(
+    vars: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> Result<String, ConvertError> {
+    let vars = checked(vars)?;
+    if vars.is_empty() {
+        return Ok("    environment: {}\n".to_string());
+    }
+    let mut block = "    environment:\n".to_string();
+    for (key, value) in vars {
+        block.push_str(&format!(
+            "      {}: {}\n",
+            json_string(key),
+            json_string(&value.replace('$', "$$"))
+        ));
+    }
+    Ok(block)
+}
+
+/// Parses a file the way `docker run --env-file` does, to show what a container will see:
+/// - lines are trimmed on the left, and lines starting with `#` are comments
+/// - everything after the first `=` is the value, so quotes, `#` and trailing whitespace are kept
+/// - a line holding only a key takes the value of that variable from `host`, and is skipped if
+///   `host` returns `None`, like `docker run -e KEY`
+///
+/// Keys containing spaces or tabs and lines without a key are errors, as they are for Docker.
+/// ```rust
+/// # use dotenv_lib::parse_docker_env_file;
+/// let env = parse_docker_env_file("A='quoted' # kept\nUSER\nMISSING\n", |key| {
+///     (key == "USER").then(|| "me".to_string())
+/// })
+/// .unwrap();
+/// assert_eq!(env.get_str("A"), Some("'quoted' # kept"));
+/// assert_eq!(env.get_str("USER"), Some("me"));
+/// assert!(!env.contains_key("MISSING"));
+/// ```
+pub fn parse_docker_env_file(
+    contents: &str,
+    host: impl Fn(&str) -> Option<String>,
+) -> Result<ParsedEnv, EnvError> {
+    let mut entries = Vec::new();
+    let contents = contents.strip_prefix('\u{feff}').unwrap_or(contents);
+    for (n, line) in contents.lines().enumerate() {
+        let line_number = n as u64 + 1;
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let (key, value) = match trimmed.split_once('=') {
+            Some((key, value)) => (key, Some(value.to_string())),
+            None => (trimmed, None),
+        };
+        if key.is_empty() {
+            return Err(EnvError::MissingKey { line: line_number });
+        }
+        if let Some(position) = key.find([' ', '\t']) {
+            let indent = line.len() - trimmed.len();
+            return Err(EnvError::UnexpectedToken {
+                expected: "a key without whitespace".to_string(),
+                found: key[position..].chars().take(1).collect(),
+                line: line_number,
+                character: (line[..indent + position].chars().count() + 1) as u64,
+            });
+        }
+        if let Some(value) = value.or_else(|| host(key)) {
+            entries.push(EnvEntry {
+                key: key.to_string(),
+                value,
+                line: line_number,
+                annotations: Annotations::default(),
+            });
+        }
+    }
+    Ok(ParsedEnv::from_entries(entries))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ConvertError, EnvError, compose_environment, docker_env_file, docker_run_args,
+        dockerfile_env, parse_docker_env_file, parse_env,
+    };
+
+    const VARS: [(&str, &str); 3] = [
+        ("PLAIN", "value"),
+        ("QUOTED", "it's \"$HOME\" \\n"),
+        ("SPACED", " a # b "),
+    ];
+
+    #[test]
+    fn quote_for_each_format() {
+        assert_eq!(
+            docker_run_args(VARS).unwrap(),
+            [
+                "-e",
+                "PLAIN=value",
+                "-e",
+                "QUOTED=it's \"$HOME\" \\n",
+                "-e",
+                "SPACED= a # b "
+            ]
+        );
+        assert_eq!(
+            docker_env_file(VARS).unwrap(),
+            "PLAIN=value\nQUOTED=it's \"$HOME\" \\n\nSPACED= a # b \n"
+        );
+        assert_eq!(
+            dockerfile_env(VARS).unwrap(),
+            "ENV PLAIN=\"value\" \\\n    QUOTED=\"it's \\\"\\$HOME\\\" \\\\n\" \\\n    SPACED=\" a # b \"\n"
+        );
+        assert_eq!(
+            compose_environment(VARS).unwrap(),
+            "    environment:\n      \"PLAIN\": \"value\"\n      \"QUOTED\": \"it's \\\"$$HOME\\\" \\\\n\"\n      \"SPACED\": \" a # b \"\n"
+        );
+    }
+
+    #[test]
+    fn reject_what_docker_cannot_read() {
+        let multi_line = [("CERT", "line one\nline two")];
+        assert_eq!(
+            docker_run_args(multi_line).unwrap()[1],
+            "CERT=line one\nline two"
+        );
+        assert!(matches!(
+            docker_env_file(multi_line),
+            Err(ConvertError::Unrepresentable { .. })
+        ));
+        assert!(dockerfile_env(multi_line).is_err());
+        assert!(compose_environment(multi_line).is_ok());
+        assert!(docker_run_args([("A=B", "c")]).is_err());
+        assert_eq!(dockerfile_env([]).unwrap(), "");
+    }
+
+    #[test]
+    fn parse_like_docker() {
+        let contents = "\u{feff}# comment\n  A=\"double\" \r\nB=a=b # not a comment\n\t\nC=\nHOME\nNOT_SET\nA=again\n";
+        let env = parse_docker_env_file(contents, |key| {
+            (key == "HOME").then(|| "/home/me".to_string())
+        })
+        .unwrap();
+        let values: Vec<(&str, &str, u64)> = env
+            .entries()
+            .iter()
+            .map(|e| (e.key.as_str(), e.value.as_str(), e.line))
+            .collect();
+        assert_eq!(
+            values,
+            [
+                ("B", "a=b # not a comment", 3),
+                ("C", "", 5),
+                ("HOME", "/home/me", 6),
+                ("A", "again", 8)
+            ]
+        );
+        assert_eq!(
+            parse_docker_env_file("A=1\n  MY KEY=x\n", |_| None),
+            Err(EnvError::UnexpectedToken {
+                expected: "a key without whitespace".to_string(),
+                found: " ".to_string(),
+                line: 2,
+                character: 5,
+            })
+        );
+        assert_eq!(
+            parse_docker_env_file("=x\n", |_| None),
+            Err(EnvError::MissingKey { line: 1 })
+        );
+
+        // a file written by `docker_env_file` reads back the values it was given
+        let env = parse_env("A='it s' # note\nB=\"x#y\"\n".to_string()).unwrap();
+        let vars = env
+            .entries()
+            .iter()
+            .map(|e| (e.key.as_str(), e.value.as_str()));
+        let file = docker_env_file(vars).unwrap();
+        assert_eq!(
+            parse_docker_env_file(&file, |_| None).unwrap().to_map(),
+            env.to_map()
+        );
+    }
+}
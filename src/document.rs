@@ -0,0 +1,376 @@
+// a lossless view of a `.env` file that keeps comments, blank lines and formatting,
+// so that files can be edited or rewritten without disturbing the lines that are not touched
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use crate::internals::{lex_dot_env, parse_dot_env_entries};
+use crate::shell::is_variable_name;
+use crate::{Annotations, EnvEntry, EnvError, ParsedEnv, quote_value, write_atomically};
+
+/// A key-value entry in an [`EnvDocument`], along with its original text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocEntry {
+    pub key: String,
+    pub value: String,
+    /// the line the entry starts on in the parsed file, or 0 for entries added afterwards
+    pub line: u64,
+    pub annotations: Annotations,
+    // the entry exactly as written, which may span several lines for quoted values
+    raw: String,
+    // whatever follows the value on its last line, such as ` # comment`
+    suffix: String,
+}
+
+impl DocEntry {
+    /// Builds an entry that is written as `KEY=value`, quoting the value as needed. Keys must start
+    /// with a letter or underscore and contain only letters, numbers and underscores.
+    pub fn new(key: impl Into<String>, value: impl Into<String>) -> Result<Self, String> {
+        let key = key.into();
+        if !is_variable_name(&key) {
+            return Err(format!("'{key}' is not a valid key"));
+        }
+        let mut entry = DocEntry {
+            key,
+            value: String::new(),
+            line: 0,
+            annotations: Annotations::default(),
+            raw: String::new(),
+            suffix: String::new(),
+        };
+        entry.set_value(value)?;
+        Ok(entry)
+    }
+
+    /// The entry exactly as it is written in the file.
+    pub fn raw(&self) -> &str {
+        &self.raw
+    }
+
+    /// Whatever follows the value on its last line, such as ` # comment`.
+    pub fn suffix(&self) -> &str {
+        &self.suffix
+    }
+
+    /// The key, value, line and annotations of this entry, without its formatting.
+    pub fn to_entry(&self) -> EnvEntry {
+        EnvEntry {
+            key: self.key.clone(),
+            value: self.value.clone(),
+            line: self.line,
+            annotations: self.annotations.clone(),
+        }
+    }
+
+    /// rewrites the entry with canonical quoting and a single space before any trailing comment
+    pub(crate) fn normalize(&mut self) {
+        let comment = self.suffix.trim();
+        self.suffix = if comment.is_empty() {
+            String::new()
+        } else {
+            format!(" {comment}")
+        };
+        // a value that was parsed can always be written back, so this only fails for entries
+        // built by hand, which are left as they are
+        let _ = self.set_value(self.value.clone());
+    }
+
+    /// Replaces the value, keeping the key and any comment after the value.
+    pub fn set_value(&mut self, value: impl Into<String>) -> Result<(), String> {
+        let value = value.into();
+        let quoted = quote_value(&value)?;
+        self.raw = format!("{}={quoted}{}", self.key, self.suffix);
+        self.value = value;
+        Ok(())
+    }
+}
+
+/// A single line, or multi-line entry, of an [`EnvDocument`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum DocLine {
+    Blank,
+    /// a comment line, including the leading `#`
+    Comment(String),
+    Entry(DocEntry),
+}
+
+impl fmt::Display for DocLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DocLine::Blank => Ok(()),
+            DocLine::Comment(text) => write!(f, "{text}"),
+            DocLine::Entry(entry) => write!(f, "{}", entry.raw),
+        }
+    }
+}
+
+/// A parsed `.env` file that keeps every comment, blank line and the exact text of each entry,
+/// so that writing it back out reproduces the original file.
+/// ```rust
+/// # use dotenv_lib::EnvDocument;
+/// let contents = "# database\nDB_URL='postgres://db' # local\n\nPORT=8080\n";
+/// let mut document = EnvDocument::parse(contents).unwrap();
+/// assert_eq!(document.to_string(), contents);
+///
+/// document.get_mut("DB_URL").unwrap().set_value("postgres://prod").unwrap();
+/// assert_eq!(document.to_string(), "# database\nDB_URL=postgres://prod # local\n\nPORT=8080\n");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EnvDocument {
+    pub lines: Vec<DocLine>,
+    /// whether the last line ends with a newline
+    pub trailing_newline: bool,
+}
+
+/// finds how many lines after `lines[0]` an entry for `key` continues onto because of a quoted
+/// value, and the text that follows the value on its last line
+fn entry_extent(lines: &[&str], key: &str) -> (usize, String) {
+    let after_key = &lines[0][key.len() + 1..];
+    let Some(quote @ ('\'' | '"')) = after_key.chars().next() else {
+        let end = after_key.find([' ', '#']).unwrap_or(after_key.len());
+        return (0, after_key[end..].to_string());
+    };
+    if let Some(close) = after_key[1..].find(quote) {
+        return (0, after_key[close + 2..].to_string());
+    }
+    for (extra, line) in lines.iter().enumerate().skip(1) {
+        if let Some(close) = line.find(quote) {
+            return (extra, line[close + 1..].to_string());
+        }
+    }
+    (lines.len() - 1, String::new())
+}
+
+impl EnvDocument {
+    /// Parses `contents` with the same rules as [`process_dot_env`](crate::process_dot_env),
+    /// keeping the text around each entry.
+    pub fn parse(contents: &str) -> Result<Self, EnvError> {
+        let entries: Vec<EnvEntry> = parse_dot_env_entries(lex_dot_env(contents.to_string()))?;
+        let mut entries = entries.into_iter().peekable();
+        let trailing_newline = contents.ends_with('\n');
+        let physical: Vec<&str> = contents
+            .strip_suffix('\n')
+            .unwrap_or(contents)
+            .split('\n')
+            .collect();
+        let physical: &[&str] = if contents.is_empty() { &[] } else { &physical };
+
+        let mut lines = Vec::new();
+        let mut index = 0;
+        while index < physical.len() {
+            let text = physical[index];
+            let line_number = index as u64 + 1;
+            match entries.next_if(|e| e.line == line_number) {
+                Some(entry) => {
+                    let (extra, suffix) = entry_extent(&physical[index..], &entry.key);
+                    let raw = physical[index..=index + extra].join("\n");
+                    lines.push(DocLine::Entry(DocEntry {
+                        key: entry.key,
+                        value: entry.value,
+                        line: entry.line,
+                        annotations: entry.annotations,
+                        raw,
+                        suffix,
+                    }));
+                    index += extra + 1;
+                }
+                None => {
+                    lines.push(if text.is_empty() {
+                        DocLine::Blank
+                    } else {
+                        DocLine::Comment(text.to_string())
+                    });
+                    index += 1;
+                }
+            }
+        }
+        Ok(EnvDocument {
+            lines,
+            trailing_newline,
+        })
+    }
+
+    /// Returns the entries in file order, including any duplicates.
+    pub fn entries(&self) -> impl Iterator<Item = &DocEntry> {
+        self.lines.iter().filter_map(|line| match line {
+            DocLine::Entry(entry) => Some(entry),
+            _ => None,
+        })
+    }
+
+    /// Returns the last entry for `key`, which is the one [`process_dot_env`](crate::process_dot_env) keeps.
+    pub fn get(&self, key: &str) -> Option<&DocEntry> {
+        self.entries().filter(|e| e.key == key).last()
+    }
+
+    /// Returns the last entry for `key` to be edited in place.
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut DocEntry> {
+        self.lines.iter_mut().rev().find_map(|line| match line {
+            DocLine::Entry(entry) if entry.key == key => Some(entry),
+            _ => None,
+        })
+    }
+
+    /// The entries of the document without their formatting, keeping the last entry for each key.
+    pub fn to_parsed_env(&self) -> ParsedEnv {
+        ParsedEnv::from_entries(self.entries().map(DocEntry::to_entry).collect())
+    }
+
+    /// Sets `key` to `value`, editing the last entry for the key in place or appending a new entry
+    /// at the end of the document. Returns true if an existing entry was changed.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<bool, String> {
+        if let Some(entry) = self.get_mut(key) {
+            entry.set_value(value)?;
+            return Ok(true);
+        }
+        let entry = DocEntry::new(key, value)?;
+        if self.lines.is_empty() {
+            self.trailing_newline = true;
+        }
+        self.lines.push(DocLine::Entry(entry));
+        Ok(false)
+    }
+
+    /// Removes every entry for `key`, along with the annotation comments directly above each one so
+    /// they do not attach to the next entry. Returns true if anything was removed.
+    pub fn remove(&mut self, key: &str) -> bool {
+        let mut removed = false;
+        while let Some(index) = self
+            .lines
+            .iter()
+            .position(|l| matches!(l, DocLine::Entry(e) if e.key == key))
+        {
+            let annotations = self.lines[..index]
+                .iter()
+                .rev()
+                .take_while(|l| matches!(l, DocLine::Comment(c) if c.trim_start_matches('#').trim_start().starts_with('@')))
+                .count();
+            self.lines.drain(index - annotations..=index);
+            removed = true;
+        }
+        removed
+    }
+
+    /// Appends a line to the end of the document.
+    pub fn push(&mut self, line: DocLine) {
+        self.lines.push(line);
+    }
+
+    /// Writes the document to `path`, replacing the file in one step so an interrupted write
+    /// cannot leave it half written. An existing file keeps its permissions, and on unix a new
+    /// file is created readable only by its owner.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), io::Error> {
+        write_atomically(path.as_ref(), &self.to_string())
+    }
+}
+
+impl fmt::Display for EnvDocument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, line) in self.lines.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{line}")?;
+        }
+        if self.trailing_newline && !self.lines.is_empty() {
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::{DocEntry, DocLine, EnvDocument};
+
+    /// writing a parsed document back out reproduces the file exactly
+    #[test]
+    fn round_trip_is_lossless() {
+        for contents in [
+            "",
+            "A=1",
+            "A=1\n",
+            "# only a comment\n",
+            "# header\n\nA='x y' # trailing\nB=\"multi\nline\" # after\n\n\n# footer\nC=3\n",
+        ] {
+            let document = EnvDocument::parse(contents).expect("error processing env file");
+            assert_eq!(document.to_string(), contents);
+        }
+    }
+
+    #[test]
+    fn lines_are_classified() {
+        let document = EnvDocument::parse("# c\n\nA='1\n2' # s\nB=2\n").unwrap();
+        assert_eq!(document.lines.len(), 4);
+        assert_eq!(document.lines[0], DocLine::Comment("# c".to_string()));
+        assert_eq!(document.lines[1], DocLine::Blank);
+        let a = document.get("A").unwrap();
+        assert_eq!(a.value, "1\n2");
+        assert_eq!(a.suffix(), " # s");
+        assert_eq!(document.get("B").unwrap().line, 5);
+    }
+
+    #[test]
+    fn edit_and_append_entries() {
+        let mut document = EnvDocument::parse("A=1 # keep me\n").unwrap();
+        document
+            .get_mut("A")
+            .unwrap()
+            .set_value("two words")
+            .unwrap();
+        document.push(DocLine::Entry(DocEntry::new("B", "it's").unwrap()));
+        assert_eq!(
+            document.to_string(),
+            "A='two words' # keep me\nB=\"it's\"\n"
+        );
+    }
+
+    #[test]
+    fn set_and_remove_keys() {
+        let mut document =
+            EnvDocument::parse("# app\nA=1 # one\n# @secret\nB=2\nC=3\nB=4\n").unwrap();
+        assert!(document.set("A", "x y").unwrap());
+        assert!(!document.set("D", "4").unwrap());
+        assert!(document.remove("B"));
+        assert!(!document.remove("B"));
+        assert_eq!(document.to_string(), "# app\nA='x y' # one\nC=3\nD=4\n");
+        assert!(document.set("1A", "x").is_err());
+        assert!(!document.set("_PRIVATE", "x").unwrap());
+        let reparsed = EnvDocument::parse(&document.to_string()).unwrap();
+        assert_eq!(reparsed.get("_PRIVATE").unwrap().value, "x");
+        assert!(document.set("A B", "x").is_err());
+        let mut empty = EnvDocument::default();
+        empty.set("A", "1").unwrap();
+        assert_eq!(empty.to_string(), "A=1\n");
+    }
+
+    #[test]
+    fn save_replaces_file() {
+        let path = "./tests/TestSave.env";
+        fs::write(path, "OLD=1\n").expect("error writing test file");
+        let document = EnvDocument::parse("# saved\nNEW=2\n").unwrap();
+        document.save(path).expect("error saving document");
+        assert_eq!(fs::read_to_string(path).unwrap(), "# saved\nNEW=2\n");
+        fs::remove_file(path).expect("error removing test file");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn save_keeps_files_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mode = |path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        let path = "./tests/TestSavePrivate.env";
+        let _ = fs::remove_file(path);
+        let document = EnvDocument::parse("SECRET=1\n").unwrap();
+        document.save(path).expect("error saving document");
+        assert_eq!(mode(path), 0o600);
+
+        fs::set_permissions(path, fs::Permissions::from_mode(0o640)).unwrap();
+        document.save(path).expect("error saving document");
+        assert_eq!(mode(path), 0o640);
+        fs::remove_file(path).expect("error removing test file");
+    }
+}
@@ -0,0 +1,65 @@
+// producing a committable `.env.example` from a real `.env` file
+use crate::{DocLine, EnvDocument};
+
+impl EnvDocument {
+    /// Returns a copy of the document suitable for committing as a `.env.example`.
+    ///
+    /// Comments, blank lines and the order of entries are kept. Entries annotated `@safe` keep their
+    /// value, entries with a `@default` annotation take the default, and every other value is replaced
+    /// by `placeholder`. Entries annotated `@secret` are always replaced, even if also marked `@safe`.
+    /// ```rust
+    /// # use dotenv_lib::EnvDocument;
+    /// let env = EnvDocument::parse("# @safe\nPORT=8080\n\n# @secret\nTOKEN=abc123 # api\n").unwrap();
+    /// let example = env.to_example("changeme").unwrap();
+    /// assert_eq!(example.to_string(), "# @safe\nPORT=8080\n\n# @secret\nTOKEN=changeme # api\n");
+    /// ```
+    pub fn to_example(&self, placeholder: &str) -> Result<EnvDocument, String> {
+        let mut example = self.clone();
+        for line in &mut example.lines {
+            let DocLine::Entry(entry) = line else {
+                continue;
+            };
+            let annotations = &entry.annotations;
+            if annotations.has("safe") && !annotations.is_secret() {
+                continue;
+            }
+            let value = match annotations.default_value() {
+                Some(default) if !default.is_empty() && !annotations.is_secret() => {
+                    default.to_string()
+                }
+                _ => placeholder.to_string(),
+            };
+            entry.set_value(value)?;
+        }
+        Ok(example)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::EnvDocument;
+
+    #[test]
+    fn mask_values_and_keep_layout() {
+        let env = EnvDocument::parse(
+            "# server\n# @safe\nHOST=localhost\n# @default 8080\nPORT=3000\n\n# credentials\n# @secret\n# @safe\nTOKEN='multi\nline' # rotate monthly\nPASSWORD=hunter2\n",
+        )
+        .expect("error processing env file");
+        let example = env.to_example("changeme").unwrap();
+        assert_eq!(
+            example.to_string(),
+            "# server\n# @safe\nHOST=localhost\n# @default 8080\nPORT=8080\n\n# credentials\n# @secret\n# @safe\nTOKEN=changeme # rotate monthly\nPASSWORD=changeme\n"
+        );
+    }
+
+    /// placeholders that need quoting are quoted, and ones that cannot be written are an error
+    #[test]
+    fn placeholder_is_quoted() {
+        let env = EnvDocument::parse("A=1\n").unwrap();
+        assert_eq!(
+            env.to_example("<your value>").unwrap().to_string(),
+            "A='<your value>'\n"
+        );
+        assert!(env.to_example("").is_err());
+    }
+}
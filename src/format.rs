@@ -0,0 +1,137 @@
+// rewriting env files into one canonical style
+use crate::{DocLine, EnvDocument, EnvError};
+
+/// How [`EnvDocument::formatted`] orders entries.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum KeyOrder {
+    /// keep entries in the order they are written
+    #[default]
+    Preserve,
+    /// sort entries by key within each section, where sections are separated by blank lines and
+    /// keep their leading comments as a header
+    Sorted,
+}
+
+/// whether a comment line is an annotation such as `# @secret`, which belongs to the entry below it
+fn is_annotation(line: &DocLine) -> bool {
+    matches!(line, DocLine::Comment(text) if text.trim_start_matches('#').trim_start().starts_with('@'))
+}
+
+/// sorts the entries of a section by key, keeping the comments directly above each entry with it
+/// and leaving a leading header and any trailing comments in place
+fn sort_section(section: Vec<DocLine>) -> Vec<DocLine> {
+    let header_len = section
+        .iter()
+        .take_while(|l| matches!(l, DocLine::Comment(_)) && !is_annotation(l))
+        .count();
+    let mut lines = section.into_iter();
+    let mut sorted: Vec<DocLine> = lines.by_ref().take(header_len).collect();
+    let mut items: Vec<(String, Vec<DocLine>)> = Vec::new();
+    let mut pending = Vec::new();
+    for line in lines {
+        match line {
+            DocLine::Entry(ref entry) => {
+                let key = entry.key.clone();
+                pending.push(line);
+                items.push((key, std::mem::take(&mut pending)));
+            }
+            other => pending.push(other),
+        }
+    }
+    items.sort_by(|(a, _), (b, _)| a.cmp(b));
+    sorted.extend(items.into_iter().flat_map(|(_, lines)| lines));
+    sorted.extend(pending);
+    sorted
+}
+
+impl EnvDocument {
+    /// Returns the document in canonical style: values quoted only when needed, one space before
+    /// trailing comments, no trailing whitespace on comments, single blank lines between sections,
+    /// no blank lines at the start or end, and a final newline.
+    /// ```rust
+    /// # use dotenv_lib::{EnvDocument, KeyOrder};
+    /// let document = EnvDocument::parse("\nB='b'   # note\n\n\nA=\"two words\"").unwrap();
+    /// assert_eq!(
+    ///     document.formatted(KeyOrder::Preserve).to_string(),
+    ///     "B=b # note\n\nA='two words'\n"
+    /// );
+    /// ```
+    pub fn formatted(&self, order: KeyOrder) -> EnvDocument {
+        let mut sections: Vec<Vec<DocLine>> = vec![Vec::new()];
+        for line in &self.lines {
+            let line = match line {
+                DocLine::Blank => {
+                    if sections.last().is_some_and(|s| !s.is_empty()) {
+                        sections.push(Vec::new());
+                    }
+                    continue;
+                }
+                DocLine::Comment(text) => DocLine::Comment(text.trim_end().to_string()),
+                DocLine::Entry(entry) => {
+                    let mut entry = entry.clone();
+                    entry.normalize();
+                    DocLine::Entry(entry)
+                }
+            };
+            if let Some(section) = sections.last_mut() {
+                section.push(line);
+            }
+        }
+        sections.retain(|s| !s.is_empty());
+        let mut lines = Vec::new();
+        for (i, section) in sections.into_iter().enumerate() {
+            if i > 0 {
+                lines.push(DocLine::Blank);
+            }
+            match order {
+                KeyOrder::Preserve => lines.extend(section),
+                KeyOrder::Sorted => lines.extend(sort_section(section)),
+            }
+        }
+        EnvDocument {
+            trailing_newline: !lines.is_empty(),
+            lines,
+        }
+    }
+}
+
+/// Parses `contents` and returns it in the canonical style of [`EnvDocument::formatted`].
+/// ```rust
+/// # use dotenv_lib::{format_env, KeyOrder};
+/// let formatted = format_env("# app\nPORT='8080'\nHOST=localhost", KeyOrder::Sorted).unwrap();
+/// assert_eq!(formatted, "# app\nHOST=localhost\nPORT=8080\n");
+/// ```
+pub fn format_env(contents: &str, order: KeyOrder) -> Result<String, EnvError> {
+    Ok(EnvDocument::parse(contents)?.formatted(order).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{KeyOrder, format_env};
+
+    #[test]
+    fn canonical_style() {
+        let contents = "\n\n# header   \nA=\"plain\"#note\nB='it s'\n\n\n\nC='x=y'   # eq\n\n";
+        let formatted =
+            format_env(contents, KeyOrder::Preserve).expect("error processing env file");
+        assert_eq!(
+            formatted,
+            "# header\nA=plain #note\nB='it s'\n\nC='x=y' # eq\n"
+        );
+        // formatting is idempotent
+        assert_eq!(
+            format_env(&formatted, KeyOrder::Preserve).unwrap(),
+            formatted
+        );
+        assert_eq!(format_env("", KeyOrder::Preserve).unwrap(), "");
+    }
+
+    #[test]
+    fn sort_within_sections() {
+        let contents = "# server\nPORT=80\n# @secret\nAPI_KEY=x\nHOST=h\n# end\n\n# database\nDB_URL=u\nDB_NAME=n\n";
+        assert_eq!(
+            format_env(contents, KeyOrder::Sorted).unwrap(),
+            "# server\n# @secret\nAPI_KEY=x\nHOST=h\nPORT=80\n# end\n\n# database\nDB_NAME=n\nDB_URL=u\n"
+        );
+    }
+}
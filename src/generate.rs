@@ -0,0 +1,74 @@
+// random values for keys annotated `@generate`, such as secrets a new checkout needs
+use std::io;
+
+use crate::base64;
+
+const ALPHANUMERIC: &[u8; 62] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+/// reads `count` bytes from the operating system's random number generator
+pub(crate) fn random_bytes(count: usize) -> Result<Vec<u8>, io::Error> {
+    let mut bytes = vec![0; count];
+    getrandom::fill(&mut bytes).map_err(|err| io::Error::other(err.to_string()))?;
+    Ok(bytes)
+}
+
+/// Generates a random value from a `@generate` argument:
+/// - `hex:N` for N random bytes written as hex, so `hex:32` gives 64 characters
+/// - `base64:N` for N random bytes written as base64
+/// - `alnum:N` for N random letters and digits
+/// ```rust
+/// # use dotenv_lib::generate_value;
+/// let value = generate_value("hex:16").unwrap();
+/// assert_eq!(value.len(), 32);
+/// assert!(generate_value("words:3").is_err());
+/// ```
+pub fn generate_value(spec: &str) -> Result<String, String> {
+    let (kind, length) = spec
+        .split_once(':')
+        .ok_or_else(|| format!("expected KIND:LENGTH, found '{spec}'"))?;
+    let length: usize = match length.trim().parse() {
+        Ok(0) | Err(_) => return Err(format!("invalid length '{length}'")),
+        Ok(length) => length,
+    };
+    let random =
+        |count| random_bytes(count).map_err(|err| format!("cannot read random bytes: {err}"));
+    match kind.trim() {
+        "hex" => Ok(random(length)?.iter().map(|b| format!("{b:02x}")).collect()),
+        "base64" => Ok(base64::encode(&random(length)?)),
+        "alnum" => {
+            let mut value = String::with_capacity(length);
+            while value.len() < length {
+                // bytes at or above 248 are skipped so that every character is equally likely
+                for b in random(length)? {
+                    if b < 248 && value.len() < length {
+                        value.push(ALPHANUMERIC[b as usize % 62] as char);
+                    }
+                }
+            }
+            Ok(value)
+        }
+        other => Err(format!(
+            "unknown kind '{other}', expected hex, base64 or alnum"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generate_value;
+
+    #[test]
+    fn generated_values() {
+        let hex = generate_value("hex:32").unwrap();
+        assert_eq!(hex.len(), 64);
+        assert!(hex.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(hex, generate_value("hex:32").unwrap());
+        assert_eq!(generate_value("base64:3").unwrap().len(), 4);
+        let alnum = generate_value("alnum:40").unwrap();
+        assert_eq!(alnum.len(), 40);
+        assert!(alnum.chars().all(|c| c.is_ascii_alphanumeric()));
+        for spec in ["hex", "hex:0", "hex:x", "uuid:4"] {
+            assert!(generate_value(spec).is_err(), "{spec}");
+        }
+    }
+}
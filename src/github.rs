@@ -0,0 +1,109 @@
+// writing variables for GitHub Actions, through the `$GITHUB_ENV` file and workflow commands
+use crate::ParsedEnv;
+#[cfg(feature = "generate")]
+use crate::generate::random_bytes;
+use crate::looks_like_secret_key;
+#[cfg(feature = "generate")]
+use crate::shell::is_variable_name;
+
+/// returns a delimiter that does not appear in `value`, in the style of GitHub's own toolkit
+#[cfg(feature = "generate")]
+fn heredoc_delimiter(value: &str) -> Result<String, String> {
+    loop {
+        let bytes = random_bytes(16).map_err(|err| format!("cannot read random bytes: {err}"))?;
+        let hex: String = bytes.iter().map(|b| format!("{b:02x}")).collect();
+        let delimiter = format!("ghadelimiter_{hex}");
+        if !value.contains(&delimiter) {
+            return Ok(delimiter);
+        }
+    }
+}
+
+/// Writes variables in the format of the file named by `$GITHUB_ENV`, which sets them for the
+/// later steps of a GitHub Actions job. Single-line values are written as `K=v`, and values
+/// holding a line break as a `K<<DELIMITER` heredoc with a random delimiter that does not occur
+/// in the value. Keys must be valid shell variable names.
+/// ```rust
+/// # use dotenv_lib::github_env_file;
+/// let file = github_env_file([("PORT", "8080"), ("MOTD", "line one\nline two")]).unwrap();
+/// let lines: Vec<&str> = file.lines().collect();
+/// assert_eq!(lines[0], "PORT=8080");
+/// let delimiter = lines[1].strip_prefix("MOTD<<").unwrap();
+/// assert_eq!(lines[2..], ["line one", "line two", delimiter]);
+/// ```
+#[cfg(feature = "generate")]
+pub fn github_env_file<'a>(
+    vars: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> Result<String, String> {
+    let mut file = String::new();
+    for (key, value) in vars {
+        if !is_variable_name(key) {
+            return Err(format!("'{key}' is not a valid variable name"));
+        }
+        if value.contains(['\n', '\r']) {
+            let delimiter = heredoc_delimiter(value)?;
+            file.push_str(&format!("{key}<<{delimiter}\n{value}\n{delimiter}\n"));
+        } else {
+            file.push_str(&format!("{key}={value}\n"));
+        }
+    }
+    Ok(file)
+}
+
+/// Writes an `::add-mask::` workflow command for each value in `env` annotated `@secret` or with
+/// a key that looks like a credential, so that GitHub Actions hides it in the job log. Printed to
+/// standard output, these must run before the values are used. Multi-line values are masked a
+/// line at a time, since the log is.
+/// ```rust
+/// # use dotenv_lib::{github_masks, parse_env};
+/// let env = parse_env("PORT=8080\n# @secret\nSESSION=abc\n".to_string()).unwrap();
+/// assert_eq!(github_masks(&env), "::add-mask::abc\n");
+/// ```
+pub fn github_masks(env: &ParsedEnv) -> String {
+    let mut masks = String::new();
+    for entry in env.entries() {
+        if !(entry.annotations.is_secret() || looks_like_secret_key(&entry.key)) {
+            continue;
+        }
+        for line in entry.value.lines().filter(|l| !l.trim().is_empty()) {
+            // workflow command values escape `%` and line breaks as URL-style escapes
+            let line = line.replace('%', "%25").replace('\r', "%0D");
+            masks.push_str(&format!("::add-mask::{line}\n"));
+        }
+    }
+    masks
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{github_masks, parse_env};
+
+    #[cfg(feature = "generate")]
+    #[test]
+    fn write_env_file() {
+        use super::heredoc_delimiter;
+        use crate::github_env_file;
+
+        let file = github_env_file([("A", "1"), ("B", "x\ny"), ("C", "= b")]).unwrap();
+        let lines: Vec<&str> = file.lines().collect();
+        let delimiter = lines[1].strip_prefix("B<<").unwrap();
+        assert!(delimiter.starts_with("ghadelimiter_"));
+        assert_eq!(lines, ["A=1", lines[1], "x", "y", delimiter, "C== b"]);
+        assert!(github_env_file([("NOT VALID", "x")]).is_err());
+
+        let value = heredoc_delimiter("").unwrap();
+        assert_ne!(heredoc_delimiter(&value).unwrap(), value);
+    }
+
+    #[test]
+    fn mask_secret_values() {
+        let env = parse_env(
+            "USER=me\nDB_PASSWORD='100%'\n# @secret\nCERT=\"line one\n\nline two\"\n".to_string(),
+        )
+        .unwrap();
+        assert_eq!(
+            github_masks(&env),
+            "::add-mask::100%25\n::add-mask::line one\n::add-mask::line two\n"
+        );
+    }
+}
@@ -0,0 +1,70 @@
+// minimal JSON output helpers, so that reports and schemas can be written without extra dependencies
+use crate::ParsedEnv;
+
+/// quotes and escapes `value` as a JSON string
+pub(crate) fn json_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// writes a JSON array of strings on one line
+pub(crate) fn json_string_array<'a>(values: impl IntoIterator<Item = &'a str>) -> String {
+    let items: Vec<String> = values.into_iter().map(json_string).collect();
+    format!("[{}]", items.join(", "))
+}
+
+/// writes a JSON object of string values, one member per line
+pub(crate) fn json_string_object<'a>(
+    members: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> String {
+    let members: Vec<String> = members
+        .into_iter()
+        .map(|(name, value)| format!("  {}: {}", json_string(name), json_string(value)))
+        .collect();
+    if members.is_empty() {
+        return "{}".to_string();
+    }
+    format!("{{\n{}\n}}", members.join(",\n"))
+}
+
+impl ParsedEnv {
+    /// Writes the entries as a JSON object of strings, in file order.
+    /// ```rust
+    /// # use dotenv_lib::parse_env;
+    /// let env = parse_env("A=1\nB='two words'\n".to_string()).unwrap();
+    /// assert_eq!(env.to_json(), "{\n  \"A\": \"1\",\n  \"B\": \"two words\"\n}");
+    /// ```
+    pub fn to_json(&self) -> String {
+        json_string_object(
+            self.entries()
+                .iter()
+                .map(|e| (e.key.as_str(), e.value.as_str())),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{json_string, json_string_array, json_string_object};
+
+    #[test]
+    fn escape_json_strings() {
+        assert_eq!(json_string("a\"b\\c\nd\u{1}"), "\"a\\\"b\\\\c\\nd\\u0001\"");
+        assert_eq!(json_string_array(["x", "y"]), "[\"x\", \"y\"]");
+        assert_eq!(json_string_object([]), "{}");
+        assert_eq!(json_string_object([("k", "v")]), "{\n  \"k\": \"v\"\n}");
+    }
+}
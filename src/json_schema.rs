@@ -0,0 +1,270 @@
+// exporting a declared or annotated schema as a JSON Schema document
+use crate::json::{json_string, json_string_array};
+use crate::{KeySpec, Schema, ValueType};
+
+const BOOL_WORDS: [&str; 8] = ["1", "0", "true", "false", "yes", "no", "on", "off"];
+
+/// 1 to 65535 as `u16` parsing reads them, with an optional `+` and leading zeros
+const PORT_PATTERN: &str = "^\\+?0*([1-9][0-9]{0,3}|[1-5][0-9]{4}|6[0-4][0-9]{3}|65[0-4][0-9]{2}|655[0-2][0-9]|6553[0-5])$";
+
+/// matches the numbers written with as many digits as `max` that are not greater than it
+fn at_most(max: &str) -> String {
+    let mut alternatives = Vec::new();
+    for (i, digit) in max.char_indices() {
+        let rest = match max.len() - i - 1 {
+            0 => String::new(),
+            rest => format!("[0-9]{{{rest}}}"),
+        };
+        match digit {
+            '0' => {}
+            '1' => alternatives.push(format!("{}0{rest}", &max[..i])),
+            _ => alternatives.push(format!(
+                "{}[0-{}]{rest}",
+                &max[..i],
+                (digit as u8 - 1) as char
+            )),
+        }
+    }
+    alternatives.push(max.to_string());
+    alternatives.join("|")
+}
+
+/// the boolean words in any case, with surrounding whitespace since values are trimmed before
+/// they are read
+fn bool_pattern() -> String {
+    let words: Vec<String> = BOOL_WORDS.iter().map(|w| case_insensitive(w)).collect();
+    format!("^\\s*({})\\s*$", words.join("|"))
+}
+
+/// the integers `i64` parsing reads: an optional sign, leading zeros, and no overflow
+fn int_pattern() -> String {
+    let max = i64::MAX.to_string();
+    let min = i64::MIN.unsigned_abs().to_string();
+    let shorter = format!("[0-9]{{1,{}}}", max.len() - 1);
+    format!(
+        "^(\\+?0*({shorter}|{})|-0*({shorter}|{}))$",
+        at_most(&max),
+        at_most(&min)
+    )
+}
+
+/// matches `word` in any case without relying on regex flags, which JSON Schema does not support
+fn case_insensitive(word: &str) -> String {
+    word.chars()
+        .map(|c| {
+            if c.is_ascii_alphabetic() {
+                format!("[{}{}]", c.to_ascii_lowercase(), c.to_ascii_uppercase())
+            } else {
+                c.to_string()
+            }
+        })
+        .collect()
+}
+
+/// the JSON Schema keywords for a single key, without the surrounding braces
+fn property_keywords(spec: &KeySpec) -> Vec<(&'static str, String)> {
+    // every value in a `.env` file is a string, so types are expressed as constraints on strings
+    let mut keywords = vec![("type", json_string("string"))];
+    match &spec.value_type {
+        ValueType::String => {}
+        ValueType::Int => keywords.push(("pattern", json_string(&int_pattern()))),
+        ValueType::Bool => keywords.push(("pattern", json_string(&bool_pattern()))),
+        ValueType::Url => keywords.push(("format", json_string("uri"))),
+        ValueType::Port => keywords.push(("pattern", json_string(PORT_PATTERN))),
+        ValueType::Enum(options) => keywords.push((
+            "enum",
+            json_string_array(options.iter().map(String::as_str)),
+        )),
+        #[cfg(feature = "regex")]
+        ValueType::Pattern(pattern) => keywords.push((
+            "pattern",
+            json_string(&format!("^(?:{})$", pattern.as_str())),
+        )),
+    }
+    if let Some(description) = &spec.description {
+        keywords.push(("description", json_string(description)));
+    }
+    if let Some(default) = &spec.default {
+        keywords.push(("default", json_string(default)));
+    }
+    if spec.secret {
+        keywords.push(("writeOnly", "true".to_string()));
+    }
+    keywords
+}
+
+impl Schema {
+    /// Writes the schema as a JSON Schema (draft 2020-12) document describing an object of string values,
+    /// so that non-Rust tooling can validate the same keys. Keys that are required and have no default
+    /// are listed as required, and secret keys are marked `writeOnly`.
+    /// ```rust
+    /// # use dotenv_lib::{Schema, parse_env};
+    /// let example = parse_env("# @type port\n# @required\nPORT=8080\n".to_string()).unwrap();
+    /// let json = Schema::from_annotations(&example).unwrap().to_json_schema();
+    /// assert!(json.contains("\"required\": [\"PORT\"]"));
+    /// ```
+    pub fn to_json_schema(&self) -> String {
+        let properties: Vec<String> = self
+            .keys()
+            .iter()
+            .map(|spec| {
+                let keywords: Vec<String> = property_keywords(spec)
+                    .into_iter()
+                    .map(|(name, value)| format!("      {}: {value}", json_string(name)))
+                    .collect();
+                format!(
+                    "    {}: {{\n{}\n    }}",
+                    json_string(&spec.key),
+                    keywords.join(",\n")
+                )
+            })
+            .collect();
+        let required = self
+            .keys()
+            .iter()
+            .filter(|spec| spec.required && spec.default.is_none())
+            .map(|spec| spec.key.as_str());
+
+        let mut json = String::from("{\n");
+        json.push_str("  \"$schema\": \"https://json-schema.org/draft/2020-12/schema\",\n");
+        json.push_str("  \"type\": \"object\",\n");
+        if properties.is_empty() {
+            json.push_str("  \"properties\": {},\n");
+        } else {
+            json.push_str(&format!(
+                "  \"properties\": {{\n{}\n  }},\n",
+                properties.join(",\n")
+            ));
+        }
+        json.push_str(&format!(
+            "  \"required\": {}\n",
+            json_string_array(required)
+        ));
+        json.push_str("}\n");
+        json
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{KeySpec, Schema, ValueType, parse_env};
+
+    #[test]
+    fn json_schema_from_declared_schema() {
+        let schema = Schema::new()
+            .key(
+                KeySpec::new("PORT", ValueType::Port)
+                    .required()
+                    .default("8080"),
+            )
+            .key(
+                KeySpec::new("API_URL", ValueType::Url)
+                    .required()
+                    .description("Base \"API\" URL"),
+            )
+            .key(KeySpec::new("TOKEN", ValueType::String).secret());
+        let expected = r#"{
+  "$schema": "https://json-schema.org/draft/2020-12/schema",
+  "type": "object",
+  "properties": {
+    "PORT": {
+      "type": "string",
+      "pattern": "^\\+?0*([1-9][0-9]{0,3}|[1-5][0-9]{4}|6[0-4][0-9]{3}|65[0-4][0-9]{2}|655[0-2][0-9]|6553[0-5])$",
+      "default": "8080"
+    },
+    "API_URL": {
+      "type": "string",
+      "format": "uri",
+      "description": "Base \"API\" URL"
+    },
+    "TOKEN": {
+      "type": "string",
+      "writeOnly": true
+    }
+  },
+  "required": ["API_URL"]
+}
+"#;
+        assert_eq!(schema.to_json_schema(), expected);
+    }
+
+    #[test]
+    fn json_schema_from_annotated_example() {
+        let example = parse_env(
+            "# @type enum(debug|info)\n# @description log verbosity\nLOG_LEVEL=info\n# @type bool\nDEBUG=no\n"
+                .to_string(),
+        )
+        .expect("error processing env file");
+        let json = Schema::from_annotations(&example).unwrap().to_json_schema();
+        assert!(json.contains("\"enum\": [\"debug\", \"info\"]"));
+        assert!(json.contains("\"description\": \"log verbosity\""));
+        assert!(json.contains("[yY][eE][sS]"));
+        assert!(json.contains("\"required\": []"));
+    }
+
+    /// the exported patterns accept exactly the values the Rust checks accept
+    #[cfg(feature = "regex")]
+    #[test]
+    fn patterns_match_rust_checks() {
+        use super::{PORT_PATTERN, bool_pattern, int_pattern};
+
+        let samples = [
+            "0",
+            "5",
+            "+5",
+            "-5",
+            "-0",
+            "+0",
+            "007",
+            "08080",
+            "+80",
+            "1",
+            "65535",
+            "65536",
+            "065535",
+            "+",
+            "-",
+            "",
+            "1.5",
+            " 5",
+            "5 ",
+            "--5",
+            "+-5",
+            "9223372036854775807",
+            "9223372036854775808",
+            "-9223372036854775808",
+            "-9223372036854775809",
+            "0009223372036854775807",
+            "99999999999999999999",
+            "yes",
+            " YES ",
+            "On",
+            "maybe",
+            "\tno\n",
+        ];
+        let cases = [
+            (ValueType::Int, int_pattern()),
+            (ValueType::Port, PORT_PATTERN.to_string()),
+            (ValueType::Bool, bool_pattern()),
+        ];
+        for (value_type, pattern) in cases {
+            let pattern = regex::Regex::new(&pattern).unwrap();
+            for sample in samples {
+                assert_eq!(
+                    pattern.is_match(sample),
+                    value_type.check(sample).is_ok(),
+                    "{value_type} disagrees on {sample:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn empty_schema() {
+        assert!(
+            Schema::new()
+                .to_json_schema()
+                .contains("\"properties\": {},")
+        );
+    }
+}
@@ -0,0 +1,201 @@
+// Kubernetes ConfigMap and Secret manifests, written from and read back into env files
+#[cfg(feature = "yaml")]
+use crate::EnvMap;
+use crate::base64;
+use crate::json::json_string;
+use crate::{ConvertError, ParsedEnv, looks_like_secret_key};
+
+/// whether `key` can be used as a key in the `data` of a ConfigMap or Secret
+fn is_valid_data_key(key: &str) -> bool {
+    !key.is_empty()
+        && key.len() <= 253
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// writes one manifest. strings are written as JSON strings, which YAML reads as double quoted
+/// scalars, so no value can be mistaken for a number, a boolean or YAML syntax
+fn manifest(kind: &str, name: &str, namespace: Option<&str>, data: &[(&str, String)]) -> String {
+    let mut manifest = format!(
+        "apiVersion: v1\nkind: {kind}\nmetadata:\n  name: {}\n",
+        json_string(name)
+    );
+    if let Some(namespace) = namespace {
+        manifest.push_str(&format!("  namespace: {}\n", json_string(namespace)));
+    }
+    if kind == "Secret" {
+        manifest.push_str("type: Opaque\n");
+    }
+    if data.is_empty() {
+        manifest.push_str("data: {}\n");
+    } else {
+        manifest.push_str("data:\n");
+        for (key, value) in data {
+            manifest.push_str(&format!("  {}: {}\n", json_string(key), json_string(value)));
+        }
+    }
+    manifest
+}
+
+/// Writes a Kubernetes `ConfigMap` holding the entries of `env`, followed by a `Secret` holding
+/// the secret entries with their values base64 encoded. An entry is secret when it is annotated
+/// `@secret` or its key looks like a credential, such as `DB_PASSWORD`. Both objects are called
+/// `name`, and the `Secret` is left out when there are no secret entries.
+/// ```rust
+/// # use dotenv_lib::{kubernetes_manifests, parse_env};
+/// let env = parse_env("PORT=8080\n# @secret\nSESSION=abc\n".to_string()).unwrap();
+/// let manifests = kubernetes_manifests(&env, "web", None).unwrap();
+/// assert!(manifests.contains("kind: ConfigMap\n"));
+/// assert!(manifests.contains("  \"PORT\": \"8080\"\n"));
+/// assert!(manifests.contains("  \"SESSION\": \"YWJj\"\n"));
+/// ```
+pub fn kubernetes_manifests(
+    env: &ParsedEnv,
+    name: &str,
+    namespace: Option<&str>,
+) -> Result<String, ConvertError> {
+    let mut config = Vec::new();
+    let mut secret = Vec::new();
+    for entry in env.entries() {
+        if !is_valid_data_key(&entry.key) {
+            return Err(ConvertError::Unrepresentable {
+                key: entry.key.clone(),
+                reason: "Kubernetes keys may only hold letters, digits, '-', '_' and '.'"
+                    .to_string(),
+            });
+        }
+        if entry.annotations.is_secret() || looks_like_secret_key(&entry.key) {
+            secret.push((entry.key.as_str(), base64::encode(entry.value.as_bytes())));
+        } else {
+            config.push((entry.key.as_str(), entry.value.clone()));
+        }
+    }
+    let mut manifests = manifest("ConfigMap", name, namespace, &config);
+    if !secret.is_empty() {
+        manifests.push_str("---\n");
+        manifests.push_str(&manifest("Secret", name, namespace, &secret));
+    }
+    Ok(manifests)
+}
+
+/// reads the string values of the mapping under `field`, if there is one
+#[cfg(feature = "yaml")]
+fn string_data(
+    object: &serde_yaml::Value,
+    field: &str,
+) -> Result<Vec<(String, String)>, ConvertError> {
+    use serde_yaml::Value;
+    let invalid = |message: String| ConvertError::Parse {
+        format: "Kubernetes manifest",
+        message,
+    };
+    match object.get(field) {
+        None | Some(Value::Null) => Ok(Vec::new()),
+        Some(Value::Mapping(data)) => data
+            .iter()
+            .map(|(key, value)| match (key, value) {
+                (Value::String(key), Value::String(value)) => Ok((key.clone(), value.clone())),
+                (Value::String(key), _) => Err(invalid(format!("'{field}.{key}' is not a string"))),
+                _ => Err(invalid(format!("'{field}' has a key that is not a string"))),
+            })
+            .collect(),
+        Some(_) => Err(invalid(format!("'{field}' is not a mapping"))),
+    }
+}
+
+/// Reads the `data` of every `ConfigMap` and the `data` and `stringData` of every `Secret` in a
+/// YAML stream of Kubernetes manifests into one env map, decoding the base64 secret values. Other
+/// kinds of object are skipped, and later objects override keys set by earlier ones.
+/// ```rust
+/// # use dotenv_lib::kubernetes_to_env_map;
+/// let manifests = "kind: ConfigMap\ndata:\n  PORT: \"8080\"\n---\nkind: Secret\ndata:\n  SESSION: YWJj\n";
+/// let env = kubernetes_to_env_map(manifests).unwrap();
+/// assert_eq!(env["PORT"], "8080");
+/// assert_eq!(env["SESSION"], "abc");
+/// ```
+#[cfg(feature = "yaml")]
+pub fn kubernetes_to_env_map(contents: &str) -> Result<EnvMap, ConvertError> {
+    use serde::Deserialize;
+    let mut env = EnvMap::new();
+    for document in serde_yaml::Deserializer::from_str(contents) {
+        let object =
+            serde_yaml::Value::deserialize(document).map_err(|err| ConvertError::Parse {
+                format: "Kubernetes manifest",
+                message: err.to_string(),
+            })?;
+        match object.get("kind").and_then(|kind| kind.as_str()) {
+            Some("ConfigMap") => env.extend(string_data(&object, "data")?),
+            Some("Secret") => {
+                for (key, value) in string_data(&object, "data")? {
+                    let value = base64::decode(&value)
+                        .and_then(|bytes| String::from_utf8(bytes).ok())
+                        .ok_or_else(|| ConvertError::Unrepresentable {
+                            key: key.clone(),
+                            reason: "the secret value is not base64 encoded UTF-8".to_string(),
+                        })?;
+                    env.insert(key, value);
+                }
+                env.extend(string_data(&object, "stringData")?);
+            }
+            _ => {}
+        }
+    }
+    Ok(env)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{kubernetes_manifests, parse_env};
+
+    #[test]
+    fn write_config_map_and_secret() {
+        let env = parse_env(
+            "PORT=8080\nDEBUG=true\n# @secret\nSESSION='a \"b\"'\nDB_PASSWORD=hunter2\n"
+                .to_string(),
+        )
+        .unwrap();
+        assert_eq!(
+            kubernetes_manifests(&env, "web", Some("prod")).unwrap(),
+            "apiVersion: v1\nkind: ConfigMap\nmetadata:\n  name: \"web\"\n  namespace: \"prod\"\n\
+             data:\n  \"PORT\": \"8080\"\n  \"DEBUG\": \"true\"\n---\n\
+             apiVersion: v1\nkind: Secret\nmetadata:\n  name: \"web\"\n  namespace: \"prod\"\n\
+             type: Opaque\ndata:\n  \"SESSION\": \"YSAiYiI=\"\n  \"DB_PASSWORD\": \"aHVudGVyMg==\"\n"
+        );
+        let env = parse_env("PORT=80\n".to_string()).unwrap();
+        assert!(
+            !kubernetes_manifests(&env, "web", None)
+                .unwrap()
+                .contains("Secret")
+        );
+        let env = parse_env("my/key=1\n".to_string()).unwrap();
+        assert!(kubernetes_manifests(&env, "web", None).is_err());
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn round_trip_through_manifests() {
+        use crate::{ConvertError, kubernetes_to_env_map};
+
+        let env = parse_env(
+            "PORT=8080\nNAME=\"line one\nline two\"\nTOKEN=\"it's\"\nEMPTY_ISH=' '\n".to_string(),
+        )
+        .unwrap();
+        let manifests = kubernetes_manifests(&env, "web", None).unwrap();
+        assert_eq!(kubernetes_to_env_map(&manifests).unwrap(), env.to_map());
+
+        let manifests = "kind: Deployment\ndata:\n  IGNORED: x\n---\n\
+                         kind: Secret\ndata:\n  A: YQ==\nstringData:\n  B: b\n";
+        let env = kubernetes_to_env_map(manifests).unwrap();
+        assert_eq!(env.len(), 2);
+        assert_eq!((env["A"].as_str(), env["B"].as_str()), ("a", "b"));
+        assert!(matches!(
+            kubernetes_to_env_map("kind: Secret\ndata:\n  A: not base64\n"),
+            Err(ConvertError::Unrepresentable { .. })
+        ));
+        assert!(matches!(
+            kubernetes_to_env_map("kind: ConfigMap\ndata:\n  PORT: 8080\n"),
+            Err(ConvertError::Parse { .. })
+        ));
+    }
+}
@@ -1,10 +1,116 @@
 use std::collections::HashMap;
 use std::io::Error as IOError;
 use std::io::{BufWriter, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::{fs, io};
 
-use crate::internals::EnvError;
+pub use crate::annotations::Annotations;
+pub use crate::check::{
+    CheckReport, check_against_example, looks_like_placeholder, looks_like_secret_key,
+};
+#[cfg(feature = "derive")]
+pub use crate::config::{__private, ConfigError};
+pub use crate::convert::{ArrayStyle, ConvertError, env_map_to_env_string};
+#[cfg(feature = "json")]
+pub use crate::convert::{env_map_to_json, json_to_env_map};
+#[cfg(feature = "toml")]
+pub use crate::convert::{env_map_to_toml, toml_to_env_map};
+#[cfg(feature = "yaml")]
+pub use crate::convert::{env_map_to_yaml, yaml_to_env_map};
+#[cfg(feature = "serde")]
+pub use crate::de::{
+    DeError, from_env_map, from_env_map_with_prefix, from_parsed_env, from_parsed_env_with_prefix,
+};
+pub use crate::dialect::{
+    BareKeys, Dialect, Escapes, InlineComments, Interpolation, InterpolationDefaults, LookupOrder,
+    Quotes, UnknownEscape,
+};
+pub use crate::docker::{
+    compose_environment, docker_env_file, docker_run_args, dockerfile_env, parse_docker_env_file,
+};
+pub use crate::document::{DocEntry, DocLine, EnvDocument};
+pub use crate::format::{KeyOrder, format_env};
+#[cfg(feature = "generate")]
+pub use crate::generate::generate_value;
+#[cfg(feature = "generate")]
+pub use crate::github::github_env_file;
+pub use crate::github::github_masks;
+pub use crate::internals::EnvError;
+pub use crate::kubernetes::kubernetes_manifests;
+#[cfg(feature = "yaml")]
+pub use crate::kubernetes::kubernetes_to_env_map;
+pub use crate::lint::{LintConfig, LintDiagnostic, LintRule, lint_env, lint_json};
+pub use crate::portability::{KeyDifference, PortabilityReport, portability_report};
+#[cfg(feature = "regex")]
+pub use crate::schema::ValuePattern;
+pub use crate::schema::{KeySpec, Schema, ValueType};
+#[cfg(feature = "serde")]
+pub use crate::ser::{SerError, to_env_string, to_env_writer};
+pub use crate::shell::{Shell, shell_script};
+pub use crate::sourcing::{ShellCheckError, ShellMismatch, compare_with_sh};
+pub use crate::systemd::{parse_systemd_env_file, systemd_env_file};
+pub use crate::typed::ValueError;
+#[cfg(feature = "derive")]
+pub use dotenv_lib_derive::EnvConfig;
+
+// lets the code generated by `#[derive(EnvConfig)]` refer to this crate from inside it
+#[cfg(feature = "derive")]
+extern crate self as dotenv_lib;
+
+mod annotations;
+mod base64;
+mod check;
+#[cfg(feature = "derive")]
+mod config;
+mod convert;
+#[cfg(feature = "serde")]
+mod de;
+mod dialect;
+mod docker;
+mod document;
+mod example;
+mod format;
+#[cfg(feature = "generate")]
+mod generate;
+mod github;
+mod json;
+mod json_schema;
+mod kubernetes;
+mod lint;
+mod portability;
+mod schema;
+#[cfg(feature = "serde")]
+mod ser;
+mod shell;
+mod sourcing;
+mod sync;
+mod systemd;
+mod typed;
+
+/// separates the key of a nested value from the keys inside it, e.g. `DATABASE__URL`
+#[cfg(any(
+    feature = "serde",
+    feature = "json",
+    feature = "yaml",
+    feature = "toml"
+))]
+pub(crate) const NESTED_SEPARATOR: &str = "__";
+
+/// converts a field name such as `database_url` or `databaseUrl` to `DATABASE_URL`. the serde
+/// support and `#[derive(EnvConfig)]` both name keys with it, so they agree on every field
+#[cfg(any(feature = "serde", feature = "derive"))]
+pub(crate) fn screaming_snake_case(name: &str) -> String {
+    let mut key = String::with_capacity(name.len() + 4);
+    let mut previous_lower = false;
+    for c in name.chars() {
+        if c.is_uppercase() && previous_lower {
+            key.push('_');
+        }
+        previous_lower = c.is_lowercase() || c.is_ascii_digit();
+        key.extend(c.to_uppercase());
+    }
+    key
+}
 
 // this and the the below type may be superflouous
 pub type EnvVar = String;
@@ -14,6 +120,86 @@ pub type EnvVal = String;
 // if the above are not needed then change this to EnvMap = HashMap<String, String>
 pub type EnvMap = HashMap<EnvVar, EnvVal>;
 
+/// A single key-value pair read from a `.env` file, along with the line it starts on
+/// and the annotation comments written directly above it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnvEntry {
+    pub key: EnvVar,
+    pub value: EnvVal,
+    pub line: u64,
+    pub annotations: Annotations,
+}
+
+/// A parsed `.env` file that remembers the order and source line of every key.
+///
+/// When a key appears more than once, the last occurrence wins, matching [`process_dot_env`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParsedEnv {
+    entries: Vec<EnvEntry>,
+}
+
+impl ParsedEnv {
+    /// Builds a `ParsedEnv` from entries in file order, keeping only the last entry for each key.
+    pub fn from_entries(entries: Vec<EnvEntry>) -> Self {
+        let mut parsed = ParsedEnv::default();
+        for entry in entries {
+            parsed.entries.retain(|e| e.key != entry.key);
+            parsed.entries.push(entry);
+        }
+        parsed
+    }
+
+    /// Returns the entries in the order they appear in the file.
+    pub fn entries(&self) -> &[EnvEntry] {
+        &self.entries
+    }
+
+    /// Returns the entry for `key`, if present.
+    pub fn entry(&self, key: &str) -> Option<&EnvEntry> {
+        self.entries.iter().find(|e| e.key == key)
+    }
+
+    /// Returns the raw string value for `key`, if present.
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        self.entry(key).map(|e| e.value.as_str())
+    }
+
+    /// Returns the line `key` was read from, if present.
+    pub fn line(&self, key: &str) -> Option<u64> {
+        self.entry(key).map(|e| e.line)
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.entry(key).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Copies the entries into a plain [`EnvMap`].
+    pub fn to_map(&self) -> EnvMap {
+        self.entries
+            .iter()
+            .map(|e| (e.key.clone(), e.value.clone()))
+            .collect()
+    }
+}
+
+impl From<ParsedEnv> for EnvMap {
+    fn from(value: ParsedEnv) -> Self {
+        value
+            .entries
+            .into_iter()
+            .map(|e| (e.key, e.value))
+            .collect()
+    }
+}
+
 #[derive(Debug)]
 pub enum FindEnvError {
     Io(IOError),
@@ -57,7 +243,7 @@ impl From<EnvError> for FindEnvError {
 mod internals {
     use core::fmt;
 
-    use super::{EnvMap, EnvVal, EnvVar};
+    use super::{Annotations, EnvEntry, EnvMap, EnvVal, EnvVar};
 
     #[derive(Debug)]
     pub enum EnvToken {
@@ -71,6 +257,21 @@ mod internals {
         Whitespace,
     }
 
+    impl EnvToken {
+        /// the character this token was lexed from, or `None` for the end of a line or file
+        fn as_char(&self) -> Option<char> {
+            match self {
+                EnvToken::Character(c) => Some(*c),
+                EnvToken::AssignmentOperator => Some('='),
+                EnvToken::Comment => Some('#'),
+                EnvToken::DoubleQuoteMark => Some('"'),
+                EnvToken::SingleQuoteMark => Some('\''),
+                EnvToken::Whitespace => Some(' '),
+                EnvToken::NewLine | EnvToken::Eof => None,
+            }
+        }
+    }
+
     /// tokenizes the given `.env` file into a Vec of Tokens
     pub fn lex_dot_env(file_contents: String) -> Vec<EnvToken> {
         file_contents
@@ -158,11 +359,47 @@ mod internals {
 
     impl std::error::Error for EnvError {}
 
+    impl EnvError {
+        /// the line the error was found on
+        pub fn line(&self) -> u64 {
+            match self {
+                EnvError::UnexpectedToken { line, .. }
+                | EnvError::MissingAssignmentOperator { line, .. }
+                | EnvError::ExpectedValueButFoundAssignment { line, .. }
+                | EnvError::MissingKey { line }
+                | EnvError::MissingValue { line }
+                | EnvError::FoundOnlyKey { line }
+                | EnvError::UnclosedValue { line } => *line,
+            }
+        }
+
+        /// the character on the line the error was found at, for errors that record one
+        pub fn character(&self) -> Option<u64> {
+            match self {
+                EnvError::UnexpectedToken { character, .. }
+                | EnvError::MissingAssignmentOperator { character, .. }
+                | EnvError::ExpectedValueButFoundAssignment { character, .. } => Some(*character),
+                _ => None,
+            }
+        }
+    }
+
     /// reads the Vec of Tokens into a valid EnvMap and returns an error
     /// for specific errors
     pub fn parse_dot_env(tokens: Vec<EnvToken>) -> Result<EnvMap, EnvError> {
-        let mut new_env_map: EnvMap = EnvMap::new();
+        Ok(parse_dot_env_entries(tokens)?
+            .into_iter()
+            .map(|entry| (entry.key, entry.value))
+            .collect())
+    }
+
+    /// reads the Vec of Tokens into key-value entries in file order, recording the line
+    /// each entry starts on and the annotation comments directly above it.
+    /// duplicate keys are kept, so callers decide which one wins
+    pub fn parse_dot_env_entries(tokens: Vec<EnvToken>) -> Result<Vec<EnvEntry>, EnvError> {
+        let mut entries: Vec<EnvEntry> = Vec::new();
         let mut line_counter: u64 = 1;
+        let mut entry_line: u64 = 1;
         let mut character_counter: u64 = 1;
         let mut current_key: String = EnvVar::new();
         let mut current_value: String = EnvVal::new();
@@ -172,13 +409,27 @@ mod internals {
         let mut encountered_assignment: bool = false;
         let mut in_single_quoted_string: bool = false;
         let mut in_double_quoted_string: bool = false;
+        let mut comment_text: String = String::new();
+        let mut comment_on_own_line: bool = false;
+        let mut pending_annotations: Annotations = Annotations::default();
 
         for token in tokens {
+            // the rest of a commented line is kept as text rather than parsed,
+            // so quotes and assignment operators in comments are allowed
+            if in_a_comment && let Some(c) = token.as_char() {
+                comment_text.push(c);
+                character_counter += 1;
+                continue;
+            }
+
             match token {
                 EnvToken::Character(c) => {
                     character_counter += 1;
                     if !in_a_comment {
                         if expecting_key {
+                            if current_key.is_empty() {
+                                entry_line = line_counter;
+                            }
                             current_key.push(c);
                             continue;
                         } else if expecting_value {
@@ -268,17 +519,18 @@ mod internals {
                     }
                 }
                 EnvToken::Comment => {
-                    if in_single_quoted_string || in_double_quoted_string {
-                        if expecting_value {
-                            current_value.push('#');
-                            continue;
-                        }
+                    if (in_single_quoted_string || in_double_quoted_string) && expecting_value {
+                        current_value.push('#');
+                        continue;
                     }
+                    comment_on_own_line = current_key.is_empty() && !encountered_assignment;
                     in_a_comment = true;
                 }
                 EnvToken::NewLine => {
                     if in_single_quoted_string || in_double_quoted_string {
                         current_value.push('\n');
+                        line_counter += 1;
+                        character_counter = 0;
                         continue;
                     }
 
@@ -287,6 +539,14 @@ mod internals {
                     if (current_key.is_empty() && current_value.is_empty())
                         && !encountered_assignment
                     {
+                        // annotations on comment lines carry over to the next entry,
+                        // while a blank line discards them
+                        if in_a_comment && comment_on_own_line {
+                            pending_annotations.add_comment(&comment_text);
+                        } else if !in_a_comment {
+                            pending_annotations = Annotations::default();
+                        }
+                        comment_text.clear();
                         expecting_key = true;
                         expecting_value = false;
                         current_key.clear();
@@ -331,8 +591,13 @@ mod internals {
 
                     if !current_key.is_empty() && !current_value.is_empty() {
                         // if there is no error,
-                        // add the key and value to the map (remember to clone)
-                        new_env_map.insert(current_key.clone(), current_value.clone());
+                        // add the key and value to the entries (remember to clone)
+                        entries.push(EnvEntry {
+                            key: current_key.clone(),
+                            value: current_value.clone(),
+                            line: entry_line,
+                            annotations: std::mem::take(&mut pending_annotations),
+                        });
                     }
 
                     // and then reset the state to expect a key
@@ -341,6 +606,7 @@ mod internals {
                     current_key.clear();
                     current_value.clear();
                     in_a_comment = false;
+                    comment_text.clear();
                     line_counter += 1;
                     character_counter = 0;
                     encountered_assignment = false;
@@ -351,11 +617,16 @@ mod internals {
                 }
                 EnvToken::Eof => {
                     if in_single_quoted_string || in_double_quoted_string {
-                        return Err(EnvError::UnclosedValue { line: line_counter });
+                        return Err(EnvError::UnclosedValue { line: entry_line });
                     }
 
                     if !current_key.is_empty() && !current_value.is_empty() {
-                        new_env_map.insert(current_key.clone(), current_value.clone());
+                        entries.push(EnvEntry {
+                            key: current_key.clone(),
+                            value: current_value.clone(),
+                            line: entry_line,
+                            annotations: std::mem::take(&mut pending_annotations),
+                        });
                     }
                     // throw an error if there is a key or value missing its pair
                     if current_key.is_empty() && !current_value.is_empty() {
@@ -399,7 +670,7 @@ mod internals {
                                 character: character_counter,
                             });
                         }
-                        if current_value != "" {
+                        if !current_value.is_empty() {
                             return Err(EnvError::UnexpectedToken {
                                 expected: "value, whitespace, newline, or comment".to_string(),
                                 found: "single quotation mark".to_string(),
@@ -441,7 +712,7 @@ mod internals {
                                 character: character_counter,
                             });
                         }
-                        if current_value != "" {
+                        if !current_value.is_empty() {
                             return Err(EnvError::UnexpectedToken {
                                 expected: "value, whitespace, newline, or comment".to_string(),
                                 found: "double quotation mark".to_string(),
@@ -458,7 +729,7 @@ mod internals {
             }
         }
 
-        Ok(new_env_map)
+        Ok(entries)
     }
 }
 
@@ -486,6 +757,20 @@ pub fn process_dot_env(file_contents: String) -> Result<HashMap<String, String>,
     internals::parse_dot_env(internals::lex_dot_env(file_contents))
 }
 
+/// Parses a `.env` file like [`process_dot_env`], but keeps the order and source line of each key
+/// so that values can be read with the typed accessors on [`ParsedEnv`].
+/// ```rust
+/// # use dotenv_lib::parse_env;
+/// let env = parse_env("PORT=8080\nDEBUG=yes\n".to_string()).unwrap();
+/// assert_eq!(env.get::<u16>("PORT").unwrap(), 8080);
+/// assert!(env.get_bool("DEBUG").unwrap());
+/// assert_eq!(env.line("DEBUG"), Some(2));
+/// ```
+pub fn parse_env(file_contents: String) -> Result<ParsedEnv, EnvError> {
+    internals::parse_dot_env_entries(internals::lex_dot_env(file_contents))
+        .map(ParsedEnv::from_entries)
+}
+
 /// Serializes a hash map to a file, overwriting it if it already exists.
 ///
 /// Given a hashmap of key-value pairs called `test_map`, we can call this function as so:
@@ -510,6 +795,78 @@ pub fn serialize_new_env(file_name: String, hash_map: EnvMap) -> Result<String,
     Ok(format!("serialized to {file_name}"))
 }
 
+/// quotes a value so that `process_dot_env` reads it back unchanged. values with whitespace or
+/// special characters are single quoted, or double quoted if they contain a single quote.
+/// values that the parser cannot read back (empty, or holding both quote marks) are an error
+pub(crate) fn quote_value(value: &str) -> Result<String, String> {
+    if value.is_empty() {
+        return Err("empty values cannot be written to a .env file".to_string());
+    }
+    let needs_quotes = value
+        .chars()
+        .any(|c| c.is_whitespace() || matches!(c, '#' | '=' | '"' | '\''));
+    if !needs_quotes {
+        return Ok(value.to_string());
+    }
+    match (value.contains('\''), value.contains('"')) {
+        (false, _) => Ok(format!("'{value}'")),
+        (true, false) => Ok(format!("\"{value}\"")),
+        (true, true) => Err("values cannot contain both single and double quotes".to_string()),
+    }
+}
+
+/// replaces the file at `path` with `contents` by writing a temporary file next to it and renaming
+/// it into place, so readers never see a partly written file. an existing file keeps its
+/// permissions and a new one is only readable by its owner, since env files often hold secrets.
+/// the temporary file has those permissions from the moment it is created, and is never an
+/// existing file or link
+pub(crate) fn write_atomically(path: &Path, contents: &str) -> Result<(), io::Error> {
+    let file_name = path.file_name().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("'{}' is not a file path", path.display()),
+        )
+    })?;
+    let existing = fs::metadata(path)
+        .ok()
+        .map(|metadata| metadata.permissions());
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(existing.as_ref().map_or(0o600, |p| p.mode() & 0o7777));
+    }
+
+    let mut attempt = 0;
+    let (mut file, temp_path) = loop {
+        let temp_path = path.with_file_name(format!(
+            ".{}.{}.{attempt}.tmp",
+            file_name.to_string_lossy(),
+            std::process::id()
+        ));
+        match options.open(&temp_path) {
+            Ok(file) => break (file, temp_path),
+            // left behind by an interrupted run, or planted by someone else
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists && attempt < 100 => attempt += 1,
+            Err(err) => return Err(err),
+        }
+    };
+    let result = (|| {
+        file.write_all(contents.as_bytes())?;
+        // the mode given when creating the file is narrowed by the umask
+        if let Some(permissions) = existing {
+            fs::set_permissions(&temp_path, permissions)?;
+        }
+        file.sync_all()?;
+        fs::rename(&temp_path, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
 /// recursively searches up a filesystem looking for a filepath that ends with `.env` to parse.
 fn find_env_string(directory_to_search: Option<String>) -> Option<String> {
     let current_dir_path_buf = directory_to_search
@@ -525,7 +882,7 @@ fn find_env_string(directory_to_search: Option<String>) -> Option<String> {
                     && path
                         .file_name()
                         .and_then(|f_name| f_name.to_str())
-                        .map_or(false, |f| f.ends_with(".env"))
+                        .is_some_and(|f| f.ends_with(".env"))
             })
             .map(|entry| entry.path());
 
@@ -840,9 +1197,7 @@ mod tests {
                 assert_eq!(rs.get("Hello").unwrap(), "World")
             }
             Err(err) => match err {
-                FindEnvError::NotFound(_err) => {
-                    assert!(true);
-                }
+                FindEnvError::NotFound(_err) => {}
                 _ => {
                     panic!("Encountered unexpected error type")
                 }
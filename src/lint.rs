@@ -0,0 +1,504 @@
+// a rule-based linter for env files, reporting problems with their positions
+use std::fmt;
+use std::str::FromStr;
+
+use crate::json::json_string;
+use crate::{DocLine, EnvDocument};
+
+/// The checks [`lint_env`] can run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintRule {
+    /// the file could not be parsed
+    Syntax,
+    /// a key is set more than once
+    DuplicateKey,
+    /// a key contains lowercase letters
+    LowercaseKey,
+    /// a value holds a space but is not quoted
+    UnquotedSpace,
+    /// a line ends with spaces or tabs
+    TrailingWhitespace,
+    /// the file mixes `\n` and `\r\n` line endings
+    MixedLineEndings,
+    /// the last line does not end with a newline
+    MissingFinalNewline,
+    /// a key is not in alphabetical order within its section
+    UnorderedKey,
+    /// a value holds `$`, a backtick or a backslash that a shell sourcing the file would interpret
+    ShellQuote,
+}
+
+impl LintRule {
+    pub const ALL: [LintRule; 9] = [
+        LintRule::Syntax,
+        LintRule::DuplicateKey,
+        LintRule::LowercaseKey,
+        LintRule::UnquotedSpace,
+        LintRule::TrailingWhitespace,
+        LintRule::MixedLineEndings,
+        LintRule::MissingFinalNewline,
+        LintRule::UnorderedKey,
+        LintRule::ShellQuote,
+    ];
+
+    /// The name used in output, configuration and suppression comments, such as `duplicate-key`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            LintRule::Syntax => "syntax",
+            LintRule::DuplicateKey => "duplicate-key",
+            LintRule::LowercaseKey => "lowercase-key",
+            LintRule::UnquotedSpace => "unquoted-space",
+            LintRule::TrailingWhitespace => "trailing-whitespace",
+            LintRule::MixedLineEndings => "mixed-line-endings",
+            LintRule::MissingFinalNewline => "missing-final-newline",
+            LintRule::UnorderedKey => "unordered-key",
+            LintRule::ShellQuote => "shell-quote",
+        }
+    }
+}
+
+impl fmt::Display for LintRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for LintRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        LintRule::ALL
+            .into_iter()
+            .find(|rule| rule.name() == s)
+            .ok_or_else(|| format!("unknown lint rule '{s}'"))
+    }
+}
+
+/// Which rules [`lint_env`] runs. Every rule is enabled by default.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LintConfig {
+    disabled: Vec<LintRule>,
+}
+
+impl LintConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn disable(mut self, rule: LintRule) -> Self {
+        if !self.disabled.contains(&rule) {
+            self.disabled.push(rule);
+        }
+        self
+    }
+
+    pub fn is_enabled(&self, rule: LintRule) -> bool {
+        !self.disabled.contains(&rule)
+    }
+}
+
+/// A problem found by [`lint_env`], at a 1-based line and character.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintDiagnostic {
+    pub rule: LintRule,
+    pub line: u64,
+    pub column: u64,
+    pub message: String,
+}
+
+impl fmt::Display for LintDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {}: {}",
+            self.line, self.column, self.rule, self.message
+        )
+    }
+}
+
+impl LintDiagnostic {
+    fn new(rule: LintRule, line: usize, column: usize, message: String) -> Self {
+        LintDiagnostic {
+            rule,
+            line: line as u64,
+            column: column as u64,
+            message,
+        }
+    }
+}
+
+/// Writes the diagnostics of one or more files as a JSON array of objects with `file`, `line`,
+/// `column`, `rule` and `message` members.
+pub fn lint_json<'a>(files: impl IntoIterator<Item = (&'a str, &'a [LintDiagnostic])>) -> String {
+    let objects: Vec<String> = files
+        .into_iter()
+        .flat_map(|(file, diagnostics)| {
+            diagnostics.iter().map(move |d| {
+                format!(
+                    "  {{\"file\": {}, \"line\": {}, \"column\": {}, \"rule\": {}, \"message\": {}}}",
+                    json_string(file),
+                    d.line,
+                    d.column,
+                    json_string(d.rule.name()),
+                    json_string(&d.message)
+                )
+            })
+        })
+        .collect();
+    if objects.is_empty() {
+        return "[]".to_string();
+    }
+    format!("[\n{}\n]", objects.join(",\n"))
+}
+
+const DISABLE_NEXT_LINE: &str = "dotenv-lint-disable-next-line";
+const DISABLE: &str = "dotenv-lint-disable";
+
+/// the rules a suppression comment names, where an empty list means every rule
+fn directive_rules(comment: &str, directive: &str) -> Option<Vec<LintRule>> {
+    let rest = comment.trim().strip_prefix(directive)?;
+    if !rest.is_empty() && !rest.starts_with([' ', ',']) {
+        return None;
+    }
+    Some(
+        rest.split([' ', ','])
+            .filter_map(|name| name.parse().ok())
+            .collect(),
+    )
+}
+
+/// the rules suppressed on each line by `# dotenv-lint-disable-next-line`, a trailing
+/// `# dotenv-lint-disable`, or a `# dotenv-lint-disable` line that applies to the rest of the file
+struct Suppressions {
+    lines: Vec<(usize, Vec<LintRule>)>,
+    from: Vec<(usize, Vec<LintRule>)>,
+}
+
+impl Suppressions {
+    /// trailing directives are only read from the entries of a parsed `document`, after the end of
+    /// their value, so a ` #` inside a quoted value is never taken for one
+    fn find(lines: &[&str], document: Option<&EnvDocument>) -> Self {
+        let mut suppressions = Suppressions {
+            lines: Vec::new(),
+            from: Vec::new(),
+        };
+        let mut entry_lines = Vec::new();
+        for entry in document.into_iter().flat_map(EnvDocument::entries) {
+            let first = entry.line as usize;
+            let last = first + entry.raw().matches('\n').count();
+            entry_lines.push(first..=last);
+            if let Some(comment) = entry.suffix().trim_start().strip_prefix('#')
+                && let Some(rules) = directive_rules(comment, DISABLE)
+            {
+                for number in first..=last {
+                    suppressions.lines.push((number, rules.clone()));
+                }
+            }
+        }
+        for (index, text) in lines.iter().enumerate() {
+            let number = index + 1;
+            // a line inside a multi-line value is part of the value, not a comment
+            if entry_lines.iter().any(|entry| entry.contains(&number)) {
+                continue;
+            }
+            if let Some(comment) = text.trim_start().strip_prefix('#') {
+                if let Some(rules) = directive_rules(comment, DISABLE_NEXT_LINE) {
+                    suppressions.lines.push((number + 1, rules));
+                } else if let Some(rules) = directive_rules(comment, DISABLE) {
+                    suppressions.from.push((number, rules));
+                }
+            }
+        }
+        suppressions
+    }
+
+    fn suppresses(&self, diagnostic: &LintDiagnostic) -> bool {
+        let line = diagnostic.line as usize;
+        let matches = |rules: &Vec<LintRule>| rules.is_empty() || rules.contains(&diagnostic.rule);
+        self.lines
+            .iter()
+            .any(|(l, rules)| *l == line && matches(rules))
+            || self
+                .from
+                .iter()
+                .any(|(l, rules)| *l <= line && matches(rules))
+    }
+}
+
+/// the rules that look at the text of each line, so they also run on files that do not parse
+fn lint_lines(contents: &str, lines: &[&str], diagnostics: &mut Vec<LintDiagnostic>) {
+    let mut open_quote: Option<char> = None;
+    let mut first_ending: Option<bool> = None;
+    let mut mixed_reported = false;
+    for (index, raw) in lines.iter().enumerate() {
+        let number = index + 1;
+        let terminated = index + 1 < lines.len() || contents.ends_with('\n');
+        let crlf = raw.ends_with('\r');
+        let text = raw.strip_suffix('\r').unwrap_or(raw);
+        if terminated {
+            match first_ending {
+                None => first_ending = Some(crlf),
+                Some(first) if first != crlf && !mixed_reported => {
+                    mixed_reported = true;
+                    diagnostics.push(LintDiagnostic::new(
+                        LintRule::MixedLineEndings,
+                        number,
+                        text.chars().count() + 1,
+                        format!(
+                            "line ends with {} but earlier lines end with {}",
+                            if crlf { "\\r\\n" } else { "\\n" },
+                            if first { "\\r\\n" } else { "\\n" }
+                        ),
+                    ));
+                }
+                _ => {}
+            }
+        }
+
+        // lines inside a quoted value are part of the value, so only the closing line is checked
+        if let Some(quote) = open_quote {
+            if !text.contains(quote) {
+                continue;
+            }
+            open_quote = None;
+        } else if !text.starts_with('#')
+            && let Some(eq) = text.find('=')
+        {
+            let value = &text[eq + 1..];
+            match value.chars().next() {
+                Some(quote @ ('\'' | '"')) => {
+                    if !value[1..].contains(quote) {
+                        open_quote = Some(quote);
+                        continue;
+                    }
+                }
+                _ => {
+                    let value = value.split(" #").next().unwrap_or_default().trim_end();
+                    if let Some(space) = value.find([' ', '\t']) {
+                        diagnostics.push(LintDiagnostic::new(
+                            LintRule::UnquotedSpace,
+                            number,
+                            eq + space + 2,
+                            "value contains whitespace but is not quoted".to_string(),
+                        ));
+                    }
+                }
+            }
+        }
+
+        let trimmed = text.trim_end_matches([' ', '\t']);
+        if trimmed.len() < text.len() {
+            diagnostics.push(LintDiagnostic::new(
+                LintRule::TrailingWhitespace,
+                number,
+                trimmed.chars().count() + 1,
+                "line ends with whitespace".to_string(),
+            ));
+        }
+    }
+    if !contents.is_empty() && !contents.ends_with('\n') {
+        let last = lines.last().copied().unwrap_or_default();
+        diagnostics.push(LintDiagnostic::new(
+            LintRule::MissingFinalNewline,
+            lines.len(),
+            last.chars().count() + 1,
+            "file does not end with a newline".to_string(),
+        ));
+    }
+}
+
+/// the rules that look at parsed entries
+fn lint_entries(document: &EnvDocument, diagnostics: &mut Vec<LintDiagnostic>) {
+    let mut seen: Vec<(&str, u64)> = Vec::new();
+    let mut previous_key: Option<&str> = None;
+    for line in &document.lines {
+        let entry = match line {
+            DocLine::Blank => {
+                previous_key = None;
+                continue;
+            }
+            DocLine::Comment(_) => continue,
+            DocLine::Entry(entry) => entry,
+        };
+        let number = entry.line as usize;
+        if let Some((_, first)) = seen.iter().find(|(k, _)| *k == entry.key) {
+            diagnostics.push(LintDiagnostic::new(
+                LintRule::DuplicateKey,
+                number,
+                1,
+                format!("{} is already set on line {first}", entry.key),
+            ));
+        } else {
+            seen.push((&entry.key, entry.line));
+        }
+        if entry.key.chars().any(|c| c.is_lowercase()) {
+            diagnostics.push(LintDiagnostic::new(
+                LintRule::LowercaseKey,
+                number,
+                1,
+                format!(
+                    "{} contains lowercase letters, use {}",
+                    entry.key,
+                    entry.key.to_uppercase()
+                ),
+            ));
+        }
+        if let Some(previous) = previous_key
+            && entry.key.as_str() < previous
+        {
+            diagnostics.push(LintDiagnostic::new(
+                LintRule::UnorderedKey,
+                number,
+                1,
+                format!("{} should come before {previous}", entry.key),
+            ));
+        }
+        previous_key = Some(&entry.key);
+
+        let raw_value = &entry.raw()[entry.key.len() + 1..];
+        if raw_value.starts_with('\'') {
+            continue;
+        }
+        let found = entry.value.char_indices().find_map(|(i, c)| match c {
+            '$' => Some((i, "`$`, which a shell would expand")),
+            '`' => Some((i, "a backtick, which a shell would run as a command")),
+            '\\' => Some((i, "a backslash, which a shell would treat as an escape")),
+            _ => None,
+        });
+        if let Some((offset, what)) = found {
+            let before = &entry.value[..offset];
+            let line = number + before.matches('\n').count();
+            let quote_len = usize::from(raw_value.starts_with('"'));
+            let column = match before.rfind('\n') {
+                Some(newline) => before[newline + 1..].chars().count() + 1,
+                None => entry.key.chars().count() + 1 + quote_len + before.chars().count() + 1,
+            };
+            diagnostics.push(LintDiagnostic::new(
+                LintRule::ShellQuote,
+                line,
+                column,
+                format!(
+                    "value of {} contains {what}; single quote it to keep it literal",
+                    entry.key
+                ),
+            ));
+        }
+    }
+}
+
+/// Checks the contents of an env file against the enabled rules, returning the problems found in
+/// line order. Problems can be suppressed with comments naming the rules, or every rule when none
+/// are named:
+/// - `# dotenv-lint-disable-next-line unordered-key` on the line above
+/// - `KEY=value # dotenv-lint-disable lowercase-key` after the value, for the whole entry, in
+///   files that parse
+/// - `# dotenv-lint-disable shell-quote` on its own line, for the rest of the file
+///
+/// ```rust
+/// # use dotenv_lib::{lint_env, LintConfig, LintRule};
+/// let diagnostics = lint_env("PORT=80\nHOST=x\nHOST=y", &LintConfig::new());
+/// let rules: Vec<LintRule> = diagnostics.iter().map(|d| d.rule).collect();
+/// assert_eq!(
+///     rules,
+///     vec![LintRule::UnorderedKey, LintRule::DuplicateKey, LintRule::MissingFinalNewline]
+/// );
+/// assert_eq!(diagnostics[1].to_string(), "3:1: duplicate-key: HOST is already set on line 2");
+/// ```
+pub fn lint_env(contents: &str, config: &LintConfig) -> Vec<LintDiagnostic> {
+    let lines: Vec<&str> = contents
+        .strip_suffix('\n')
+        .unwrap_or(contents)
+        .split('\n')
+        .collect();
+    let lines: &[&str] = if contents.is_empty() { &[] } else { &lines };
+    let mut diagnostics = Vec::new();
+    lint_lines(contents, lines, &mut diagnostics);
+    let document = EnvDocument::parse(contents);
+    match &document {
+        Ok(document) => lint_entries(document, &mut diagnostics),
+        Err(err) => diagnostics.push(LintDiagnostic {
+            rule: LintRule::Syntax,
+            line: err.line(),
+            column: err.character().unwrap_or(1),
+            message: err.to_string(),
+        }),
+    }
+    let suppressions = Suppressions::find(lines, document.as_ref().ok());
+    diagnostics.retain(|d| config.is_enabled(d.rule) && !suppressions.suppresses(d));
+    diagnostics.sort_by_key(|d| (d.line, d.column));
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{LintConfig, LintRule, lint_env, lint_json};
+
+    fn rules(contents: &str) -> Vec<(LintRule, u64, u64)> {
+        lint_env(contents, &LintConfig::new())
+            .into_iter()
+            .map(|d| (d.rule, d.line, d.column))
+            .collect()
+    }
+
+    #[test]
+    fn clean_file() {
+        assert!(rules("# app\nA=1\nB='x y $HOME'\n\nC=\"multi\nline\"\n").is_empty());
+        assert!(rules("").is_empty());
+    }
+
+    #[test]
+    fn text_rules() {
+        assert_eq!(
+            rules("A=1\r\nB=2 \nC=hello world"),
+            vec![
+                (LintRule::TrailingWhitespace, 2, 4),
+                (LintRule::MixedLineEndings, 2, 5),
+                (LintRule::UnquotedSpace, 3, 8),
+                (LintRule::Syntax, 3, 9),
+                (LintRule::MissingFinalNewline, 3, 14),
+            ]
+        );
+    }
+
+    #[test]
+    fn entry_rules() {
+        assert_eq!(
+            rules("B=1\nA=\"$HOME\"\nlower=`x`\n\nZ=1\nZ=c:\\\\dir\n"),
+            vec![
+                (LintRule::UnorderedKey, 2, 1),
+                (LintRule::ShellQuote, 2, 4),
+                (LintRule::LowercaseKey, 3, 1),
+                (LintRule::ShellQuote, 3, 7),
+                (LintRule::DuplicateKey, 6, 1),
+                (LintRule::ShellQuote, 6, 5),
+            ]
+        );
+    }
+
+    #[test]
+    fn configure_and_suppress_rules() {
+        let contents = "B=1\n# dotenv-lint-disable-next-line unordered-key\nA=1\nlower=1 # dotenv-lint-disable\n\n# dotenv-lint-disable duplicate-key\nA=2\nC=3 \n";
+        assert_eq!(rules(contents), vec![(LintRule::TrailingWhitespace, 8, 4)]);
+        let config = LintConfig::new().disable(LintRule::TrailingWhitespace);
+        assert!(lint_env(contents, &config).is_empty());
+        assert_eq!("shell-quote".parse(), Ok(LintRule::ShellQuote));
+
+        // the directive is the comment after the value, not a ` #` inside it
+        assert!(rules("a=\"x #y\" # dotenv-lint-disable lowercase-key\n").is_empty());
+        assert_eq!(
+            rules("b=\"x # dotenv-lint-disable y\"\n"),
+            vec![(LintRule::LowercaseKey, 1, 1)]
+        );
+        assert!("nope".parse::<LintRule>().is_err());
+    }
+
+    #[test]
+    fn json_output() {
+        let diagnostics = lint_env("a=1", &LintConfig::new());
+        assert_eq!(
+            lint_json([(".env", diagnostics.as_slice())]),
+            "[\n  {\"file\": \".env\", \"line\": 1, \"column\": 1, \"rule\": \"lowercase-key\", \"message\": \"a contains lowercase letters, use A\"},\n  {\"file\": \".env\", \"line\": 1, \"column\": 4, \"rule\": \"missing-final-newline\", \"message\": \"file does not end with a newline\"}\n]"
+        );
+        assert_eq!(lint_json([]), "[]");
+    }
+}
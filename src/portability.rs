@@ -0,0 +1,179 @@
+// comparing how one file reads under this crate's parser and under other dialects
+use std::fmt;
+
+use crate::{Dialect, EnvError, ParsedEnv, parse_env};
+
+/// the name this crate's own parser has in reports
+const THIS_CRATE: &str = "dotenv_lib";
+
+/// A key that does not read as the same value everywhere, with the value each parser gives it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyDifference {
+    pub key: String,
+    /// the first line the key is set on, as read by any parser
+    pub line: u64,
+    /// the value each parser reads, or `None` where the key is not set
+    pub values: Vec<(&'static str, Option<String>)>,
+}
+
+impl fmt::Display for KeyDifference {
+    /// writes the distinct values with the parsers that read each, such as
+    /// `2: TAG: "v1" (dotenv_lib, node), "v1#beta" (python)`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut groups: Vec<(&Option<String>, Vec<&str>)> = Vec::new();
+        for (name, value) in &self.values {
+            match groups.iter_mut().find(|(v, _)| *v == value) {
+                Some((_, names)) => names.push(name),
+                None => groups.push((value, vec![name])),
+            }
+        }
+        let groups: Vec<String> = groups
+            .into_iter()
+            .map(|(value, names)| match value {
+                Some(value) => format!("{value:?} ({})", names.join(", ")),
+                None => format!("not set ({})", names.join(", ")),
+            })
+            .collect();
+        write!(f, "{}: {}: {}", self.line, self.key, groups.join(", "))
+    }
+}
+
+/// How a file reads under this crate's parser and a set of dialects.
+#[derive(Debug, PartialEq)]
+pub struct PortabilityReport {
+    /// the parsers that read the file, which the values of each difference follow
+    pub parsers: Vec<&'static str>,
+    /// the parsers that could not read the file at all
+    pub errors: Vec<(&'static str, EnvError)>,
+    /// the keys that read differently, in file order
+    pub differences: Vec<KeyDifference>,
+}
+
+impl PortabilityReport {
+    /// Whether every parser reads the file, and reads the same values.
+    pub fn is_portable(&self) -> bool {
+        self.errors.is_empty() && self.differences.is_empty()
+    }
+}
+
+impl fmt::Display for PortabilityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, err) in &self.errors {
+            writeln!(f, "{}: {name} cannot read the file: {err}", err.line())?;
+        }
+        for difference in &self.differences {
+            writeln!(f, "{difference}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads `contents` with this crate's parser and with each of `dialects`, and reports the keys
+/// that do not read as the same value under all of them, along with any parser that fails. The
+/// file is read on its own, so interpolated variables that it does not set are empty and bare
+/// keys that would be taken from the environment are not set.
+/// ```rust
+/// # use dotenv_lib::{portability_report, Dialect};
+/// let report = portability_report("COLOR='#fff'\nTHEME=dark #comment\n", &Dialect::PRESETS);
+/// assert!(report.is_portable());
+///
+/// let report = portability_report("TAG=v1#beta\n", &[Dialect::NODE, Dialect::PYTHON]);
+/// assert_eq!(
+///     report.to_string(),
+///     "1: TAG: \"v1\" (dotenv_lib, node), \"v1#beta\" (python)\n"
+/// );
+/// ```
+pub fn portability_report(contents: &str, dialects: &[Dialect]) -> PortabilityReport {
+    let mut parsed: Vec<(&'static str, ParsedEnv)> = Vec::new();
+    let mut errors = Vec::new();
+    let results = std::iter::once((THIS_CRATE, parse_env(contents.to_string()))).chain(
+        dialects
+            .iter()
+            .map(|dialect| (dialect.name, dialect.parse(contents, |_| None))),
+    );
+    for (name, result) in results {
+        match result {
+            Ok(env) => parsed.push((name, env)),
+            Err(err) => errors.push((name, err)),
+        }
+    }
+
+    let mut keys: Vec<(u64, &str)> = Vec::new();
+    for (_, env) in &parsed {
+        for entry in env.entries() {
+            match keys.iter_mut().find(|(_, key)| *key == entry.key) {
+                Some((line, _)) => *line = (*line).min(entry.line),
+                None => keys.push((entry.line, &entry.key)),
+            }
+        }
+    }
+    keys.sort();
+    let differences = keys
+        .into_iter()
+        .filter_map(|(line, key)| {
+            let values: Vec<(&'static str, Option<String>)> = parsed
+                .iter()
+                .map(|(name, env)| (*name, env.get_str(key).map(str::to_string)))
+                .collect();
+            let differs = values.iter().any(|(_, value)| *value != values[0].1);
+            differs.then(|| KeyDifference {
+                key: key.to_string(),
+                line,
+                values,
+            })
+        })
+        .collect();
+    PortabilityReport {
+        parsers: parsed.iter().map(|(name, _)| *name).collect(),
+        errors,
+        differences,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Dialect, EnvError, portability_report};
+
+    #[test]
+    fn report_differences() {
+        let contents = "HOST=db\nURL=\"pg://${HOST}/app\"\nTAG=v1#beta\nMOTD=\"hi\\tthere\"\n";
+        let report = portability_report(contents, &Dialect::PRESETS);
+        assert_eq!(
+            report.parsers,
+            ["dotenv_lib", "node", "python", "ruby", "compose"]
+        );
+        assert_eq!(
+            report.errors,
+            [(
+                "dotenvy",
+                EnvError::UnexpectedToken {
+                    expected: "a known escape sequence".to_string(),
+                    found: "\\t".to_string(),
+                    line: 4,
+                    character: 6,
+                }
+            )]
+        );
+        let keys: Vec<&str> = report.differences.iter().map(|d| d.key.as_str()).collect();
+        assert_eq!(keys, ["URL", "TAG", "MOTD"]);
+        assert_eq!(
+            report.to_string(),
+            "4: dotenvy cannot read the file: Unexpected token: expected a known escape sequence but found '\\t' at line 4, character 6\n\
+             2: URL: \"pg://${HOST}/app\" (dotenv_lib, node), \"pg://db/app\" (python, ruby, compose)\n\
+             3: TAG: \"v1\" (dotenv_lib, node, ruby), \"v1#beta\" (python, compose)\n\
+             4: MOTD: \"hi\\\\tthere\" (dotenv_lib, node), \"hi\\tthere\" (python, compose), \"hitthere\" (ruby)\n"
+        );
+        assert!(!report.is_portable());
+
+        // keys only some parsers read are reported as not set by the others
+        let report = portability_report("export A=1\n", &[Dialect::NODE]);
+        assert_eq!(report.errors.len(), 1);
+        assert!(report.differences.is_empty());
+        let report = portability_report("A\nB=1\n", &[Dialect::RUBY, Dialect::NODE]);
+        assert_eq!(
+            report.to_string(),
+            "1: dotenv_lib cannot read the file: Only found key on line 1, expected assignment operator and value\n\
+             1: A: \"\" (ruby), not set (node)\n"
+        );
+    }
+}
@@ -0,0 +1,464 @@
+// declaring the keys a `.env` file is expected to hold, and validating parsed files against them
+use std::fmt;
+use std::str::FromStr;
+
+use crate::typed::parse_bool;
+use crate::{EnvEntry, EnvMap, ParsedEnv, ValueError};
+
+/// The kind of value a key is expected to hold.
+#[derive(Debug, Clone)]
+pub enum ValueType {
+    String,
+    Int,
+    Bool,
+    Url,
+    Port,
+    Enum(Vec<String>),
+    #[cfg(feature = "regex")]
+    Pattern(ValuePattern),
+}
+
+/// A regular expression that a whole value must match, for [`ValueType::Pattern`].
+#[cfg(feature = "regex")]
+#[derive(Debug, Clone)]
+pub struct ValuePattern {
+    source: String,
+    /// the pattern wrapped in `^(?:...)$`, so every alternative is tried against the whole value
+    anchored: regex::Regex,
+}
+
+#[cfg(feature = "regex")]
+impl ValuePattern {
+    pub fn new(pattern: &str) -> Result<Self, regex::Error> {
+        // compiled on its own first, so a pattern such as `a)|(b` is not accepted once wrapped
+        regex::Regex::new(pattern)?;
+        Ok(ValuePattern {
+            source: pattern.to_string(),
+            anchored: regex::Regex::new(&format!("^(?:{pattern})$"))?,
+        })
+    }
+
+    /// The pattern as it was written, without the anchors.
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Whether the pattern matches the whole of `value`.
+    pub fn is_match(&self, value: &str) -> bool {
+        self.anchored.is_match(value)
+    }
+}
+
+impl fmt::Display for ValueType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValueType::String => write!(f, "string"),
+            ValueType::Int => write!(f, "int"),
+            ValueType::Bool => write!(f, "bool"),
+            ValueType::Url => write!(f, "url"),
+            ValueType::Port => write!(f, "port"),
+            ValueType::Enum(options) => write!(f, "enum({})", options.join("|")),
+            #[cfg(feature = "regex")]
+            ValueType::Pattern(pattern) => write!(f, "pattern({})", pattern.as_str()),
+        }
+    }
+}
+
+/// Reads the names used by `@type` annotations: `string`, `int`, `bool`, `url`, `port`,
+/// `enum(a|b|c)`, and `pattern(...)` with the `regex` feature.
+impl FromStr for ValueType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(options) = s.strip_prefix("enum(").and_then(|o| o.strip_suffix(')')) {
+            let options: Vec<String> = options
+                .split('|')
+                .map(|o| o.trim().to_string())
+                .filter(|o| !o.is_empty())
+                .collect();
+            if options.is_empty() {
+                return Err("enum types need at least one option".to_string());
+            }
+            return Ok(ValueType::Enum(options));
+        }
+        if let Some(pattern) = s.strip_prefix("pattern(").and_then(|p| p.strip_suffix(')')) {
+            #[cfg(feature = "regex")]
+            return ValuePattern::new(pattern)
+                .map(ValueType::Pattern)
+                .map_err(|err| err.to_string());
+            #[cfg(not(feature = "regex"))]
+            return Err(format!("pattern({pattern}) requires the `regex` feature"));
+        }
+        match s.to_ascii_lowercase().as_str() {
+            "string" | "str" => Ok(ValueType::String),
+            "int" | "integer" => Ok(ValueType::Int),
+            "bool" | "boolean" => Ok(ValueType::Bool),
+            "url" => Ok(ValueType::Url),
+            "port" => Ok(ValueType::Port),
+            _ => Err(format!("unknown type '{s}'")),
+        }
+    }
+}
+
+/// checks for a scheme followed by `://` and a non-empty host
+fn is_url(value: &str) -> bool {
+    let Some((scheme, rest)) = value.split_once("://") else {
+        return false;
+    };
+    let valid_scheme = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    valid_scheme && !host.is_empty() && !value.chars().any(char::is_whitespace)
+}
+
+impl ValueType {
+    /// Checks `value` against this type, returning the reason it does not match.
+    pub fn check(&self, value: &str) -> Result<(), String> {
+        match self {
+            ValueType::String => Ok(()),
+            ValueType::Int => value
+                .parse::<i64>()
+                .map(|_| ())
+                .map_err(|_| "expected an integer".to_string()),
+            ValueType::Bool => parse_bool(value)
+                .map(|_| ())
+                .ok_or_else(|| "expected one of 1/0, true/false, yes/no, on/off".to_string()),
+            ValueType::Url => {
+                if is_url(value) {
+                    Ok(())
+                } else {
+                    Err("expected a URL such as https://example.com".to_string())
+                }
+            }
+            ValueType::Port => match value.parse::<u16>() {
+                Ok(port) if port > 0 => Ok(()),
+                _ => Err("expected a port between 1 and 65535".to_string()),
+            },
+            ValueType::Enum(options) => {
+                if options.iter().any(|option| option == value) {
+                    Ok(())
+                } else {
+                    Err(format!("expected one of {}", options.join(", ")))
+                }
+            }
+            #[cfg(feature = "regex")]
+            ValueType::Pattern(pattern) => {
+                if pattern.is_match(value) {
+                    Ok(())
+                } else {
+                    Err(format!("expected a value matching {}", pattern.as_str()))
+                }
+            }
+        }
+    }
+}
+
+/// The declaration of a single expected key.
+#[derive(Debug, Clone)]
+pub struct KeySpec {
+    pub key: String,
+    pub value_type: ValueType,
+    pub required: bool,
+    pub default: Option<String>,
+    pub description: Option<String>,
+    pub secret: bool,
+}
+
+impl KeySpec {
+    /// Declares an optional key of the given type with no default.
+    pub fn new(key: impl Into<String>, value_type: ValueType) -> Self {
+        KeySpec {
+            key: key.into(),
+            value_type,
+            required: false,
+            default: None,
+            description: None,
+            secret: false,
+        }
+    }
+
+    /// Declares a key from the annotation comments above an entry. Keys without a `@type` are strings.
+    pub fn from_entry(entry: &EnvEntry) -> Result<Self, ValueError> {
+        let annotations = &entry.annotations;
+        let value_type: ValueType = match annotations.value_type() {
+            Some(name) => name.parse().map_err(|reason| ValueError::Invalid {
+                key: entry.key.clone(),
+                value: name.to_string(),
+                line: entry.line,
+                reason: format!("invalid @type annotation: {reason}"),
+            })?,
+            None => ValueType::String,
+        };
+        if let Some(default) = annotations.default_value() {
+            value_type
+                .check(default)
+                .map_err(|reason| ValueError::Invalid {
+                    key: entry.key.clone(),
+                    value: default.to_string(),
+                    line: entry.line,
+                    reason: format!("invalid @default annotation: {reason}"),
+                })?;
+        }
+        Ok(KeySpec {
+            key: entry.key.clone(),
+            value_type,
+            required: annotations.is_required(),
+            default: annotations.default_value().map(str::to_string),
+            description: annotations.description(),
+            secret: annotations.is_secret(),
+        })
+    }
+
+    pub fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    /// Sets a value to use when the key is missing, so that a required key may be left out.
+    pub fn default(mut self, value: impl Into<String>) -> Self {
+        self.default = Some(value.into());
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn secret(mut self) -> Self {
+        self.secret = true;
+        self
+    }
+}
+
+/// A set of expected keys that a parsed `.env` file can be validated against.
+/// ```rust
+/// # use dotenv_lib::{KeySpec, Schema, ValueType, parse_env};
+/// let schema = Schema::new()
+///     .key(KeySpec::new("PORT", ValueType::Port).default("8080"))
+///     .key(KeySpec::new("DATABASE_URL", ValueType::Url).required())
+///     .key(KeySpec::new("LOG_LEVEL", ValueType::Enum(vec!["debug".into(), "info".into()])));
+///
+/// let env = parse_env("PORT=http\nLOG_LEVEL=loud\n".to_string()).unwrap();
+/// let errors = schema.validate(&env).unwrap_err();
+/// assert_eq!(errors.len(), 3);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Schema {
+    keys: Vec<KeySpec>,
+}
+
+impl Schema {
+    pub fn new() -> Self {
+        Schema::default()
+    }
+
+    /// Declares every key of an annotated file, such as a `.env.example`, from the annotation
+    /// comments above it, so the file itself can serve as the schema.
+    /// ```rust
+    /// # use dotenv_lib::{Schema, parse_env};
+    /// let example = parse_env("# @type port\n# @required\nPORT=8080\n".to_string()).unwrap();
+    /// let schema = Schema::from_annotations(&example).unwrap();
+    ///
+    /// let env = parse_env("PORT=http\n".to_string()).unwrap();
+    /// assert!(schema.validate(&env).is_err());
+    /// ```
+    pub fn from_annotations(env: &ParsedEnv) -> Result<Self, ValueError> {
+        env.entries()
+            .iter()
+            .try_fold(Schema::new(), |schema, entry| {
+                Ok(schema.key(KeySpec::from_entry(entry)?))
+            })
+    }
+
+    /// Adds a key to the schema, replacing any earlier declaration of the same key.
+    pub fn key(mut self, spec: KeySpec) -> Self {
+        self.keys.retain(|k| k.key != spec.key);
+        self.keys.push(spec);
+        self
+    }
+
+    /// Returns the declared keys in the order they were added.
+    pub fn keys(&self) -> &[KeySpec] {
+        &self.keys
+    }
+
+    /// Returns the declaration of `key`, if present.
+    pub fn get(&self, key: &str) -> Option<&KeySpec> {
+        self.keys.iter().find(|k| k.key == key)
+    }
+
+    /// Checks every declared key in `env`, reporting all violations together in declaration order.
+    /// Keys that are not declared are ignored, and the default of a missing key is checked against
+    /// its type in place of a value.
+    pub fn validate(&self, env: &ParsedEnv) -> Result<(), Vec<ValueError>> {
+        let errors: Vec<ValueError> = self
+            .keys
+            .iter()
+            .filter_map(|spec| match env.entry(&spec.key) {
+                Some(entry) => spec
+                    .value_type
+                    .check(&entry.value)
+                    .err()
+                    .map(|reason| ValueError::invalid(entry, reason)),
+                None => match &spec.default {
+                    Some(default) => spec.value_type.check(default).err().map(|reason| {
+                        ValueError::InvalidDefault {
+                            key: spec.key.clone(),
+                            default: default.clone(),
+                            reason,
+                        }
+                    }),
+                    None if spec.required => Some(ValueError::Missing {
+                        key: spec.key.clone(),
+                    }),
+                    None => None,
+                },
+            })
+            .collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Copies `env` into an [`EnvMap`], adding the default of every declared key that is missing.
+    pub fn apply_defaults(&self, env: &ParsedEnv) -> EnvMap {
+        let mut map = env.to_map();
+        for spec in &self.keys {
+            if let Some(default) = &spec.default {
+                map.entry(spec.key.clone())
+                    .or_insert_with(|| default.clone());
+            }
+        }
+        map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{KeySpec, Schema, ValueError, ValueType, parse_env};
+
+    fn schema() -> Schema {
+        Schema::new()
+            .key(
+                KeySpec::new("PORT", ValueType::Port)
+                    .required()
+                    .default("8080"),
+            )
+            .key(KeySpec::new("WORKERS", ValueType::Int))
+            .key(KeySpec::new("DEBUG", ValueType::Bool))
+            .key(KeySpec::new("API_URL", ValueType::Url).required())
+            .key(KeySpec::new(
+                "MODE",
+                ValueType::Enum(vec!["fast".to_string(), "safe".to_string()]),
+            ))
+    }
+
+    #[test]
+    fn valid_env_passes() {
+        let env = parse_env(
+            "WORKERS=-2\nDEBUG=yes\nAPI_URL=https://api.example.com/v1\nMODE=safe\n".to_string(),
+        )
+        .expect("error processing env file");
+        assert_eq!(schema().validate(&env), Ok(()));
+        assert_eq!(schema().apply_defaults(&env).get("PORT").unwrap(), "8080");
+    }
+
+    /// every violation is reported with the line it came from
+    #[test]
+    fn report_all_violations_with_lines() {
+        let env = parse_env("PORT=99999\nWORKERS=many\nDEBUG=perhaps\nMODE=slow\n".to_string())
+            .expect("error processing env file");
+        let errors = schema().validate(&env).unwrap_err();
+        let summary: Vec<(String, Option<u64>)> = errors
+            .into_iter()
+            .map(|err| match err {
+                ValueError::Missing { key } | ValueError::InvalidDefault { key, .. } => (key, None),
+                ValueError::Invalid { key, line, .. } => (key, Some(line)),
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("PORT".to_string(), Some(1)),
+                ("WORKERS".to_string(), Some(2)),
+                ("DEBUG".to_string(), Some(3)),
+                ("API_URL".to_string(), None),
+                ("MODE".to_string(), Some(4)),
+            ]
+        );
+    }
+
+    #[test]
+    fn schema_from_annotations() {
+        let example = parse_env(
+            "# @type enum(debug|info)\n# @default info\nLOG_LEVEL=info\n\n# @secret\n# @required\n# @description signs sessions\nSECRET_KEY=changeme\n".to_string(),
+        )
+        .expect("error processing env file");
+        let schema = Schema::from_annotations(&example).unwrap();
+        let level = schema.get("LOG_LEVEL").unwrap();
+        assert_eq!(level.value_type.to_string(), "enum(debug|info)");
+        assert_eq!(level.default.as_deref(), Some("info"));
+        let secret = schema.get("SECRET_KEY").unwrap();
+        assert!(secret.secret && secret.required);
+        assert_eq!(secret.description.as_deref(), Some("signs sessions"));
+
+        let bad = parse_env("# @type colour\nA=red\n".to_string()).unwrap();
+        assert!(matches!(
+            Schema::from_annotations(&bad),
+            Err(ValueError::Invalid { line: 2, .. })
+        ));
+        let bad = parse_env("# @type port\n# @default http\nPORT=80\n".to_string()).unwrap();
+        assert!(matches!(
+            Schema::from_annotations(&bad),
+            Err(ValueError::Invalid { line: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn invalid_default_is_reported() {
+        let schema = Schema::new().key(KeySpec::new("PORT", ValueType::Port).default("http"));
+        let env = parse_env("OTHER=1\n".to_string()).unwrap();
+        assert_eq!(
+            schema.validate(&env),
+            Err(vec![ValueError::InvalidDefault {
+                key: "PORT".to_string(),
+                default: "http".to_string(),
+                reason: "expected a port between 1 and 65535".to_string(),
+            }])
+        );
+        // the default is only checked when it would be used
+        let env = parse_env("PORT=80\n".to_string()).unwrap();
+        assert_eq!(schema.validate(&env), Ok(()));
+    }
+
+    #[test]
+    fn url_checks() {
+        assert!(
+            ValueType::Url
+                .check("postgres://user:pw@db:5432/app")
+                .is_ok()
+        );
+        assert!(ValueType::Url.check("localhost:5432").is_err());
+        assert!(ValueType::Url.check("http://").is_err());
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn pattern_must_match_whole_value() {
+        use crate::ValuePattern;
+
+        let pattern = ValueType::Pattern(ValuePattern::new("[a-z]+-[0-9]+").unwrap());
+        assert!(pattern.check("eu-1").is_ok());
+        assert!(pattern.check("eu-1x").is_err());
+        // the leftmost alternative does not stop a longer one from matching the whole value
+        let pattern = ValueType::Pattern(ValuePattern::new("a|ab").unwrap());
+        assert!(pattern.check("ab").is_ok());
+        assert!(ValuePattern::new("a)|(b").is_err());
+    }
+}
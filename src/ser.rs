@@ -0,0 +1,642 @@
+// serde serialization of config structs into `.env` files
+use std::fmt;
+use std::io::{Error as IOError, Write};
+
+use serde::Serialize;
+use serde::ser::{self, Impossible};
+
+use crate::{NESTED_SEPARATOR, quote_value, screaming_snake_case};
+
+#[derive(Debug)]
+pub enum SerError {
+    Io(IOError),
+    Unrepresentable { key: String, reason: String },
+    Message(String),
+}
+
+impl fmt::Display for SerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SerError::Io(err) => write!(f, "I/O Error: {err}"),
+            SerError::Unrepresentable { key, reason } => {
+                write!(f, "Cannot serialize key '{key}': {reason}")
+            }
+            SerError::Message(msg) => write!(f, "Serialization error: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for SerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SerError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<IOError> for SerError {
+    fn from(value: IOError) -> Self {
+        SerError::Io(value)
+    }
+}
+
+impl ser::Error for SerError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        SerError::Message(msg.to_string())
+    }
+}
+
+/// Serializes a struct or map into the contents of a `.env` file, with one `KEY=value` line per field
+/// in field order.
+///
+/// Field names become SCREAMING_SNAKE_CASE keys, nested structs are flattened with `__`, sequences are
+/// joined with commas, `None` fields are left out, and values are quoted so that
+/// [`process_dot_env`](crate::process_dot_env) reads them back unchanged. Values it cannot read
+/// back, such as empty strings and empty sequences, are reported as [`SerError::Unrepresentable`].
+/// ```rust
+/// # use dotenv_lib::to_env_string;
+/// #[derive(serde::Serialize)]
+/// struct Config {
+///     port: u16,
+///     greeting: String,
+///     database: Database,
+/// }
+///
+/// #[derive(serde::Serialize)]
+/// struct Database {
+///     url: String,
+/// }
+///
+/// let config = Config {
+///     port: 8080,
+///     greeting: "hello world".to_string(),
+///     database: Database { url: "postgres://db".to_string() },
+/// };
+/// assert_eq!(
+///     to_env_string(&config).unwrap(),
+///     "PORT=8080\nGREETING='hello world'\nDATABASE__URL=postgres://db\n"
+/// );
+/// ```
+pub fn to_env_string<T: Serialize + ?Sized>(value: &T) -> Result<String, SerError> {
+    let mut buffer = Vec::new();
+    to_env_writer(&mut buffer, value)?;
+    String::from_utf8(buffer).map_err(|err| SerError::Message(err.to_string()))
+}
+
+/// Serializes a struct or map like [`to_env_string`], writing the lines to `writer`.
+pub fn to_env_writer<W: Write, T: Serialize + ?Sized>(
+    mut writer: W,
+    value: &T,
+) -> Result<(), SerError> {
+    let mut entries = Vec::new();
+    value.serialize(FieldSerializer {
+        entries: &mut entries,
+        key: None,
+    })?;
+    for (key, value) in entries {
+        let quoted = quote_value(&value).map_err(|reason| SerError::Unrepresentable {
+            key: key.clone(),
+            reason,
+        })?;
+        writer.write_all(format!("{key}={quoted}\n").as_bytes())?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn unsupported(what: &str) -> SerError {
+    SerError::Message(format!("{what} cannot be written to a .env file"))
+}
+
+/// serializes one field into `entries` under `key`, or the top level value when `key` is `None`
+struct FieldSerializer<'a> {
+    entries: &'a mut Vec<(String, String)>,
+    key: Option<String>,
+}
+
+impl FieldSerializer<'_> {
+    fn push(self, value: String) -> Result<(), SerError> {
+        match self.key {
+            Some(key) => {
+                self.entries.push((key, value));
+                Ok(())
+            }
+            None => Err(SerError::Message(
+                "expected a struct or map at the top level".to_string(),
+            )),
+        }
+    }
+
+    fn nested_prefix(&self) -> String {
+        self.key
+            .as_ref()
+            .map(|key| format!("{key}{NESTED_SEPARATOR}"))
+            .unwrap_or_default()
+    }
+}
+
+macro_rules! serialize_scalar {
+    ($($method:ident : $ty:ty),* $(,)?) => {
+        $(
+            fn $method(self, v: $ty) -> Result<(), SerError> {
+                self.push(v.to_string())
+            }
+        )*
+    };
+}
+
+impl<'a> ser::Serializer for FieldSerializer<'a> {
+    type Ok = ();
+    type Error = SerError;
+    type SerializeSeq = SeqSerializer<'a>;
+    type SerializeTuple = SeqSerializer<'a>;
+    type SerializeTupleStruct = SeqSerializer<'a>;
+    type SerializeTupleVariant = Impossible<(), SerError>;
+    type SerializeMap = MapSerializer<'a>;
+    type SerializeStruct = MapSerializer<'a>;
+    type SerializeStructVariant = Impossible<(), SerError>;
+
+    serialize_scalar! {
+        serialize_bool: bool,
+        serialize_i8: i8,
+        serialize_i16: i16,
+        serialize_i32: i32,
+        serialize_i64: i64,
+        serialize_i128: i128,
+        serialize_u8: u8,
+        serialize_u16: u16,
+        serialize_u32: u32,
+        serialize_u64: u64,
+        serialize_u128: u128,
+        serialize_f32: f32,
+        serialize_f64: f64,
+        serialize_char: char,
+        serialize_str: &str,
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<(), SerError> {
+        Err(unsupported("byte arrays"))
+    }
+
+    fn serialize_none(self) -> Result<(), SerError> {
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), SerError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), SerError> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), SerError> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), SerError> {
+        self.push(variant.to_string())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), SerError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<(), SerError> {
+        Err(unsupported("enum variants with data"))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<SeqSerializer<'a>, SerError> {
+        match self.key {
+            Some(key) => Ok(SeqSerializer {
+                entries: self.entries,
+                key,
+                items: Vec::new(),
+            }),
+            None => Err(SerError::Message(
+                "expected a struct or map at the top level".to_string(),
+            )),
+        }
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SeqSerializer<'a>, SerError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SeqSerializer<'a>, SerError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, SerError> {
+        Err(unsupported("enum variants with data"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<MapSerializer<'a>, SerError> {
+        let prefix = self.nested_prefix();
+        Ok(MapSerializer {
+            entries: self.entries,
+            prefix,
+            next_key: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<MapSerializer<'a>, SerError> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, SerError> {
+        Err(unsupported("enum variants with data"))
+    }
+}
+
+/// serializes the fields of a struct, or the entries of a map, under a common prefix
+struct MapSerializer<'a> {
+    entries: &'a mut Vec<(String, String)>,
+    prefix: String,
+    next_key: Option<String>,
+}
+
+impl ser::SerializeStruct for MapSerializer<'_> {
+    type Ok = ();
+    type Error = SerError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerError> {
+        value.serialize(FieldSerializer {
+            entries: self.entries,
+            key: Some(format!("{}{}", self.prefix, screaming_snake_case(key))),
+        })
+    }
+
+    fn end(self) -> Result<(), SerError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for MapSerializer<'_> {
+    type Ok = ();
+    type Error = SerError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), SerError> {
+        self.next_key = Some(key.serialize(ScalarSerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerError> {
+        let key = self
+            .next_key
+            .take()
+            .ok_or_else(|| SerError::Message("map value serialized before its key".to_string()))?;
+        value.serialize(FieldSerializer {
+            entries: self.entries,
+            key: Some(format!("{}{key}", self.prefix)),
+        })
+    }
+
+    fn end(self) -> Result<(), SerError> {
+        Ok(())
+    }
+}
+
+/// joins the items of a sequence with commas
+struct SeqSerializer<'a> {
+    entries: &'a mut Vec<(String, String)>,
+    key: String,
+    items: Vec<String>,
+}
+
+impl SeqSerializer<'_> {
+    fn push_item<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerError> {
+        let item = value.serialize(ScalarSerializer)?;
+        if item.contains(',') {
+            return Err(SerError::Unrepresentable {
+                key: self.key.clone(),
+                reason: format!("list item '{item}' contains a comma"),
+            });
+        }
+        self.items.push(item);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<(), SerError> {
+        self.entries.push((self.key, self.items.join(",")));
+        Ok(())
+    }
+}
+
+impl ser::SerializeSeq for SeqSerializer<'_> {
+    type Ok = ();
+    type Error = SerError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerError> {
+        self.push_item(value)
+    }
+
+    fn end(self) -> Result<(), SerError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SeqSerializer<'_> {
+    type Ok = ();
+    type Error = SerError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerError> {
+        self.push_item(value)
+    }
+
+    fn end(self) -> Result<(), SerError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqSerializer<'_> {
+    type Ok = ();
+    type Error = SerError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerError> {
+        self.push_item(value)
+    }
+
+    fn end(self) -> Result<(), SerError> {
+        self.finish()
+    }
+}
+
+/// serializes a single scalar, such as a list item or map key, to a string
+struct ScalarSerializer;
+
+macro_rules! scalar_to_string {
+    ($($method:ident : $ty:ty),* $(,)?) => {
+        $(
+            fn $method(self, v: $ty) -> Result<String, SerError> {
+                Ok(v.to_string())
+            }
+        )*
+    };
+}
+
+impl ser::Serializer for ScalarSerializer {
+    type Ok = String;
+    type Error = SerError;
+    type SerializeSeq = Impossible<String, SerError>;
+    type SerializeTuple = Impossible<String, SerError>;
+    type SerializeTupleStruct = Impossible<String, SerError>;
+    type SerializeTupleVariant = Impossible<String, SerError>;
+    type SerializeMap = Impossible<String, SerError>;
+    type SerializeStruct = Impossible<String, SerError>;
+    type SerializeStructVariant = Impossible<String, SerError>;
+
+    scalar_to_string! {
+        serialize_bool: bool,
+        serialize_i8: i8,
+        serialize_i16: i16,
+        serialize_i32: i32,
+        serialize_i64: i64,
+        serialize_i128: i128,
+        serialize_u8: u8,
+        serialize_u16: u16,
+        serialize_u32: u32,
+        serialize_u64: u64,
+        serialize_u128: u128,
+        serialize_f32: f32,
+        serialize_f64: f64,
+        serialize_char: char,
+        serialize_str: &str,
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<String, SerError> {
+        Err(unsupported("byte arrays"))
+    }
+
+    fn serialize_none(self) -> Result<String, SerError> {
+        Err(unsupported("missing list items"))
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<String, SerError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<String, SerError> {
+        Err(unsupported("unit values in lists"))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<String, SerError> {
+        Err(unsupported("unit values in lists"))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<String, SerError> {
+        Ok(variant.to_string())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<String, SerError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<String, SerError> {
+        Err(unsupported("enum variants with data"))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, SerError> {
+        Err(unsupported("nested lists"))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, SerError> {
+        Err(unsupported("nested lists"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, SerError> {
+        Err(unsupported("nested lists"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, SerError> {
+        Err(unsupported("enum variants with data"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, SerError> {
+        Err(unsupported("maps inside lists"))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, SerError> {
+        Err(unsupported("structs inside lists"))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, SerError> {
+        Err(unsupported("enum variants with data"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde::{Deserialize, Serialize};
+
+    use crate::{SerError, from_env_map, process_dot_env, to_env_string};
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    #[serde(rename_all = "lowercase")]
+    enum Mode {
+        Fast,
+        Safe,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Limits {
+        max_body: u64,
+        burst: Option<u32>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Config {
+        name: String,
+        enabled: bool,
+        mode: Mode,
+        hosts: Vec<String>,
+        note: Option<String>,
+        limits: Limits,
+    }
+
+    fn sample() -> Config {
+        Config {
+            name: "it's a # test".to_string(),
+            enabled: true,
+            mode: Mode::Safe,
+            hosts: vec!["a".to_string(), "b".to_string()],
+            note: None,
+            limits: Limits {
+                max_body: 1024,
+                burst: Some(5),
+            },
+        }
+    }
+
+    /// fields keep their order, nested structs are flattened and None is left out
+    #[test]
+    fn serialize_in_field_order() {
+        let contents = to_env_string(&sample()).unwrap();
+        assert_eq!(
+            contents,
+            "NAME=\"it's a # test\"\nENABLED=true\nMODE=safe\nHOSTS=a,b\nLIMITS__MAX_BODY=1024\nLIMITS__BURST=5\n"
+        );
+    }
+
+    #[test]
+    fn serialize_round_trips_through_parser() {
+        let contents = to_env_string(&sample()).unwrap();
+        let map = process_dot_env(contents).expect("error processing env file");
+        let config: Config = from_env_map(&map).unwrap();
+        assert_eq!(config, sample());
+    }
+
+    /// process_dot_env rejects `KEY=`, so empty values are errors rather than lines it cannot read
+    #[test]
+    fn empty_values_err() {
+        let mut strings = BTreeMap::new();
+        strings.insert("NOTE", Some(String::new()));
+        let mut lists = BTreeMap::new();
+        lists.insert("HOSTS", Vec::<String>::new());
+        for result in [to_env_string(&strings), to_env_string(&lists)] {
+            assert!(matches!(result, Err(SerError::Unrepresentable { .. })));
+        }
+    }
+
+    #[test]
+    fn serialize_map_keeps_keys() {
+        let mut map = BTreeMap::new();
+        map.insert("lower_key", "value with space");
+        assert_eq!(
+            to_env_string(&map).unwrap(),
+            "lower_key='value with space'\n"
+        );
+    }
+
+    #[test]
+    fn unrepresentable_value_err() {
+        let mut map = BTreeMap::new();
+        map.insert("BOTH", "it's \"quoted\"");
+        match to_env_string(&map) {
+            Err(SerError::Unrepresentable { key, .. }) => assert_eq!(key, "BOTH"),
+            other => panic!("Did not return correct error: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn top_level_scalar_err() {
+        assert!(matches!(to_env_string(&5), Err(SerError::Message(_))));
+    }
+}
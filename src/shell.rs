@@ -0,0 +1,154 @@
+// exporting variables as statements for interactive shells, so `eval "$(dotenv export)"` works
+use std::fmt;
+use std::str::FromStr;
+
+/// A shell that [`shell_script`] can write statements for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    PowerShell,
+}
+
+impl fmt::Display for Shell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Shell::Bash => "bash",
+            Shell::Zsh => "zsh",
+            Shell::Fish => "fish",
+            Shell::PowerShell => "pwsh",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for Shell {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "bash" | "sh" => Ok(Shell::Bash),
+            "zsh" => Ok(Shell::Zsh),
+            "fish" => Ok(Shell::Fish),
+            "pwsh" | "powershell" => Ok(Shell::PowerShell),
+            _ => Err(format!(
+                "unknown shell '{s}', expected bash, zsh, fish or pwsh"
+            )),
+        }
+    }
+}
+
+/// whether `key` is a letter or underscore followed by letters, digits and underscores, which
+/// every shell accepts as a variable name
+pub(crate) fn is_variable_name(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// quotes a value for a POSIX shell. nothing is special inside single quotes, so a single quote
+/// is written by closing the quotes, adding an escaped quote and reopening them
+fn posix_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// quotes a value for fish, where only backslashes and single quotes are escaped inside single quotes
+fn fish_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\\', r"\\").replace('\'', r"\'"))
+}
+
+/// quotes a value for PowerShell, where single quotes are doubled inside single quotes. PowerShell
+/// also treats the typographic single quotes as quote marks, so they are doubled too
+fn powershell_quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('\'');
+    for c in value.chars() {
+        if matches!(c, '\'' | '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}') {
+            quoted.push(c);
+        }
+        quoted.push(c);
+    }
+    quoted.push('\'');
+    quoted
+}
+
+/// Writes a statement per variable that sets and exports it in `shell`, one per line:
+/// `export K='v'` for bash and zsh, `set -gx K 'v'` for fish and `$env:K = 'v'` for PowerShell.
+/// Values are single quoted, so multi-line values and values holding quotes, `$` or backslashes
+/// are kept literally. Keys must be valid shell variable names.
+/// ```rust
+/// # use dotenv_lib::{parse_env, shell_script, Shell};
+/// let env = parse_env("GREETING=\"it's here\"\n".to_string()).unwrap();
+/// let vars = env.entries().iter().map(|e| (e.key.as_str(), e.value.as_str()));
+/// assert_eq!(shell_script(vars, Shell::Bash).unwrap(), "export GREETING='it'\\''s here'\n");
+/// ```
+pub fn shell_script<'a>(
+    vars: impl IntoIterator<Item = (&'a str, &'a str)>,
+    shell: Shell,
+) -> Result<String, String> {
+    let mut script = String::new();
+    for (key, value) in vars {
+        if !is_variable_name(key) {
+            return Err(format!("'{key}' is not a valid {shell} variable name"));
+        }
+        let statement = match shell {
+            Shell::Bash | Shell::Zsh => format!("export {key}={}", posix_quote(value)),
+            Shell::Fish => format!("set -gx {key} {}", fish_quote(value)),
+            Shell::PowerShell => format!("$env:{key} = {}", powershell_quote(value)),
+        };
+        script.push_str(&statement);
+        script.push('\n');
+    }
+    Ok(script)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Shell, shell_script};
+
+    const VARS: [(&str, &str); 3] = [
+        ("PLAIN", "value"),
+        ("QUOTES", "it's \"$HOME\" \\n"),
+        ("MULTI", "line one\nline ‘two’"),
+    ];
+
+    #[test]
+    fn escape_for_each_shell() {
+        assert_eq!(
+            shell_script(VARS, Shell::Bash).unwrap(),
+            "export PLAIN='value'\nexport QUOTES='it'\\''s \"$HOME\" \\n'\nexport MULTI='line one\nline ‘two’'\n"
+        );
+        assert_eq!(
+            shell_script(VARS, Shell::Fish).unwrap(),
+            "set -gx PLAIN 'value'\nset -gx QUOTES 'it\\'s \"$HOME\" \\\\n'\nset -gx MULTI 'line one\nline ‘two’'\n"
+        );
+        assert_eq!(
+            shell_script(VARS, Shell::PowerShell).unwrap(),
+            "$env:PLAIN = 'value'\n$env:QUOTES = 'it''s \"$HOME\" \\n'\n$env:MULTI = 'line one\nline ‘‘two’’'\n"
+        );
+        assert!(shell_script([("NOT-VALID", "x")], Shell::Zsh).is_err());
+        assert_eq!("powershell".parse(), Ok(Shell::PowerShell));
+    }
+
+    /// the bash output read back by a real shell gives the original values
+    #[cfg(unix)]
+    #[test]
+    fn bash_round_trip() {
+        let script = shell_script(VARS, Shell::Bash).unwrap();
+        let output = std::process::Command::new("sh")
+            .arg("-c")
+            .arg(format!(
+                "{script}printf '%s\\0' \"$PLAIN\" \"$QUOTES\" \"$MULTI\""
+            ))
+            .output()
+            .expect("error running sh");
+        let values: Vec<&str> = std::str::from_utf8(&output.stdout)
+            .unwrap()
+            .split_terminator('\0')
+            .collect();
+        assert_eq!(values, VARS.map(|(_, v)| v));
+    }
+}
@@ -0,0 +1,265 @@
+// checking that a POSIX shell sourcing an env file sets the values this crate reads from it
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+use crate::shell::is_variable_name;
+use crate::{EnvError, ParsedEnv, parse_env};
+
+#[derive(Debug)]
+pub enum ShellCheckError {
+    Io(io::Error),
+    Env(EnvError),
+    /// the shell could not source the file, with what it wrote to standard error
+    Shell(String),
+    /// the system's `env` cannot separate variables with NUL bytes (`env -0`, a GNU extension),
+    /// with what it wrote to standard error
+    NoNulSeparator(String),
+}
+
+impl fmt::Display for ShellCheckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShellCheckError::Io(err) => write!(f, "I/O error: {err}"),
+            ShellCheckError::Env(err) => write!(f, "Env parsing error: {err}"),
+            ShellCheckError::Shell(stderr) => write!(f, "sh could not source the file: {stderr}"),
+            ShellCheckError::NoNulSeparator(stderr) => write!(
+                f,
+                "this system's env does not support `env -0`, which the check needs: {stderr}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ShellCheckError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ShellCheckError::Io(err) => Some(err),
+            ShellCheckError::Env(err) => Some(err),
+            ShellCheckError::Shell(_) | ShellCheckError::NoNulSeparator(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for ShellCheckError {
+    fn from(value: io::Error) -> Self {
+        ShellCheckError::Io(value)
+    }
+}
+
+impl From<EnvError> for ShellCheckError {
+    fn from(value: EnvError) -> Self {
+        ShellCheckError::Env(value)
+    }
+}
+
+/// A key that `/bin/sh` and this crate read differently.
+#[derive(Debug, PartialEq)]
+pub struct ShellMismatch {
+    pub key: String,
+    /// the line the key is set on, or `None` for a variable only the shell sets
+    pub line: Option<u64>,
+    /// the value this crate reads, if it sets the key, or the error for a line it cannot read
+    pub library: Result<Option<String>, EnvError>,
+    /// the value the shell sets, if it sets the key
+    pub shell: Option<String>,
+}
+
+impl fmt::Display for ShellMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let describe = |value: &Option<String>| match value {
+            Some(value) => format!("{value:?}"),
+            None => "nothing".to_string(),
+        };
+        if let Some(line) = self.line {
+            write!(f, "{line}: ")?;
+        }
+        match &self.library {
+            Ok(value) => write!(f, "{}: dotenv_lib reads {}", self.key, describe(value))?,
+            Err(err) => write!(f, "{}: dotenv_lib cannot read the line ({err})", self.key)?,
+        }
+        write!(f, ", sh sets {}", describe(&self.shell))
+    }
+}
+
+/// runs `script` in `/bin/sh` with an empty environment and returns the environment it ends with
+fn sh_environment(script: &str, args: &[&str]) -> Result<HashMap<String, String>, ShellCheckError> {
+    let output = Command::new("/bin/sh")
+        .arg("-c")
+        .arg(script)
+        .arg("sh")
+        .args(args)
+        .env_clear()
+        .output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(ShellCheckError::Shell(stderr.trim().to_string()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .split_terminator('\0')
+        .filter_map(|var| var.split_once('='))
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect())
+}
+
+/// parses `contents` line by line as far as possible: each line the parser rejects is blanked out
+/// and the rest parsed again, so one bad line does not hide the others
+fn parse_readable_lines(contents: &str) -> Result<(ParsedEnv, Vec<(String, EnvError)>), EnvError> {
+    let mut lines: Vec<String> = contents.split('\n').map(str::to_string).collect();
+    let mut rejected = Vec::new();
+    loop {
+        match parse_env(lines.join("\n")) {
+            Ok(env) => return Ok((env, rejected)),
+            Err(err) => {
+                let index = (err.line() as usize).wrapping_sub(1);
+                // a line that is already blank cannot be the problem, so give up rather than loop
+                match lines.get_mut(index).filter(|line| !line.trim().is_empty()) {
+                    Some(line) => rejected.push((std::mem::take(line), err)),
+                    None => return Err(err),
+                }
+            }
+        }
+    }
+}
+
+/// the variable a line the parser rejected would set in a shell, such as `A` for `export A=1`
+fn rejected_key(line: &str) -> String {
+    let line = line.trim_start();
+    let line = line.strip_prefix("export ").unwrap_or(line).trim_start();
+    let key = line.split_once('=').map_or(line, |(key, _)| key).trim();
+    if is_variable_name(key) {
+        key.to_string()
+    } else {
+        String::new()
+    }
+}
+
+/// Sources the file at `path` in `/bin/sh` with `set -a; . FILE; env -0`, starting from an empty
+/// environment, and compares every variable it sets with what [`parse_env`] reads from the file.
+/// Returns the lines that differ in file order, including lines this crate cannot read (such as
+/// `export A=1`), followed by variables only the shell sets. Variables the shell sets on its own,
+/// such as `PWD`, are ignored unless the file sets them too. `env -0` is a GNU extension, and
+/// [`ShellCheckError::NoNulSeparator`] is returned on systems whose `env` lacks it.
+///
+/// Sourcing runs the file as a shell script, so command substitutions in it are executed: only
+/// check files you trust.
+/// ```rust,no_run
+/// # use dotenv_lib::compare_with_sh;
+/// for mismatch in compare_with_sh(".env".as_ref()).unwrap() {
+///     println!(".env:{mismatch}");
+/// }
+/// ```
+pub fn compare_with_sh(path: &Path) -> Result<Vec<ShellMismatch>, ShellCheckError> {
+    let (env, rejected) = parse_readable_lines(&std::fs::read_to_string(path)?)?;
+    // checked first, so that a failure here is not mistaken for a problem with the file
+    let defaults = sh_environment("env -0", &[]).map_err(|err| match err {
+        ShellCheckError::Shell(stderr) => ShellCheckError::NoNulSeparator(stderr),
+        err => err,
+    })?;
+    // `.` searches PATH for names without a slash, so the path always gets one
+    let mut file = path.to_string_lossy().into_owned();
+    if !file.contains('/') {
+        file = format!("./{file}");
+    }
+    let shell = sh_environment("set -a; . \"$1\"; env -0", &[&file])?;
+
+    let mut mismatches = Vec::new();
+    for entry in env.entries() {
+        let shell_value = shell.get(&entry.key);
+        if shell_value != Some(&entry.value) {
+            mismatches.push(ShellMismatch {
+                key: entry.key.clone(),
+                line: Some(entry.line),
+                library: Ok(Some(entry.value.clone())),
+                shell: shell_value.cloned(),
+            });
+        }
+    }
+    let mut rejected_keys = Vec::new();
+    for (text, err) in rejected {
+        let key = rejected_key(&text);
+        rejected_keys.push(key.clone());
+        mismatches.push(ShellMismatch {
+            shell: shell.get(&key).cloned(),
+            key,
+            line: Some(err.line()),
+            library: Err(err),
+        });
+    }
+    mismatches.sort_by_key(|mismatch| mismatch.line);
+
+    let mut extra: Vec<(&String, &String)> = shell
+        .iter()
+        .filter(|(key, value)| {
+            !env.contains_key(key)
+                && !rejected_keys.contains(key)
+                && defaults.get(key.as_str()) != Some(value)
+        })
+        .collect();
+    extra.sort();
+    mismatches.extend(extra.into_iter().map(|(key, value)| ShellMismatch {
+        key: key.clone(),
+        line: None,
+        library: Ok(None),
+        shell: Some(value.clone()),
+    }));
+    Ok(mismatches)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::{EnvError, ShellMismatch, compare_with_sh};
+
+    #[cfg(unix)]
+    #[test]
+    fn compare_sourced_values() {
+        let path = "./tests/TestSourcing.env";
+        let contents = "PLAIN=value\nQUOTED='two words'\nMULTI=\"line one\nline two\"\nDOLLAR=\"$PLAIN-x\"\nESCAPED=\"a\\\\b\"\nCOMMENT=x#y\nexport EXPORTED=1\nAFTER=ok\n";
+        fs::write(path, contents).expect("error writing test file");
+        let mismatches = compare_with_sh(path.as_ref());
+        fs::remove_file(path).expect("error removing test file");
+        let mismatches = mismatches.expect("error sourcing test file");
+        assert_eq!(
+            mismatches,
+            [
+                ShellMismatch {
+                    key: "DOLLAR".to_string(),
+                    line: Some(5),
+                    library: Ok(Some("$PLAIN-x".to_string())),
+                    shell: Some("value-x".to_string()),
+                },
+                ShellMismatch {
+                    key: "ESCAPED".to_string(),
+                    line: Some(6),
+                    library: Ok(Some("a\\\\b".to_string())),
+                    shell: Some("a\\b".to_string()),
+                },
+                ShellMismatch {
+                    key: "COMMENT".to_string(),
+                    line: Some(7),
+                    library: Ok(Some("x".to_string())),
+                    shell: Some("x#y".to_string()),
+                },
+                ShellMismatch {
+                    key: "EXPORTED".to_string(),
+                    line: Some(8),
+                    library: Err(EnvError::UnexpectedToken {
+                        expected: "key or comment symbol".to_string(),
+                        found: " ".to_string(),
+                        line: 8,
+                        character: 7,
+                    }),
+                    shell: Some("1".to_string()),
+                },
+            ]
+        );
+        assert_eq!(
+            mismatches[0].to_string(),
+            "5: DOLLAR: dotenv_lib reads \"$PLAIN-x\", sh sets \"value-x\""
+        );
+    }
+}
@@ -0,0 +1,86 @@
+// bringing a local `.env` file up to date with keys added to its `.env.example`
+use crate::{DocLine, EnvDocument};
+
+impl EnvDocument {
+    /// Appends the keys of `example` that this document does not set, returning the keys added.
+    ///
+    /// Each added key brings the comment lines directly above it in the example, and takes its
+    /// `@default` value when it has one, or the example's value otherwise. Lines already in the
+    /// document are left untouched.
+    /// ```rust
+    /// # use dotenv_lib::EnvDocument;
+    /// let mut env = EnvDocument::parse("PORT=3000\n").unwrap();
+    /// let example = EnvDocument::parse("PORT=8080\n# @default info\nLOG_LEVEL=changeme\n").unwrap();
+    /// assert_eq!(env.sync_from_example(&example).unwrap(), vec!["LOG_LEVEL"]);
+    /// assert_eq!(env.to_string(), "PORT=3000\n\n# @default info\nLOG_LEVEL=info\n");
+    /// ```
+    pub fn sync_from_example(&mut self, example: &EnvDocument) -> Result<Vec<String>, String> {
+        let mut added: Vec<String> = Vec::new();
+        let mut appended = Vec::new();
+        for (index, line) in example.lines.iter().enumerate() {
+            let DocLine::Entry(entry) = line else {
+                continue;
+            };
+            if self.get(&entry.key).is_some() || added.contains(&entry.key) {
+                continue;
+            }
+            let comments = example.lines[..index]
+                .iter()
+                .rev()
+                .take_while(|l| matches!(l, DocLine::Comment(_)))
+                .count();
+            appended.extend(example.lines[index - comments..index].iter().cloned());
+            let mut entry = entry.clone();
+            if let Some(default) = entry.annotations.default_value()
+                && !default.is_empty()
+                && default != entry.value
+            {
+                let default = default.to_string();
+                entry.set_value(default).map_err(|reason| {
+                    format!("cannot write default for '{}': {reason}", entry.key)
+                })?;
+            }
+            entry.line = 0;
+            added.push(entry.key.clone());
+            appended.push(DocLine::Entry(entry));
+        }
+        if appended.is_empty() {
+            return Ok(added);
+        }
+        if self.lines.last().is_some_and(|l| *l != DocLine::Blank) {
+            self.lines.push(DocLine::Blank);
+        }
+        self.lines.extend(appended);
+        self.trailing_newline = true;
+        Ok(added)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::EnvDocument;
+
+    #[test]
+    fn append_missing_keys_with_comments() {
+        let mut env = EnvDocument::parse("# mine\nPORT=3000 # local\nEXTRA=1").unwrap();
+        let example = EnvDocument::parse(
+            "# server\nPORT=8080\n\n# database\n# @description connection string\nDB_URL='postgres://localhost/app'\n# @secret\nDB_PASSWORD=changeme\n",
+        )
+        .expect("error processing env file");
+        let added = env.sync_from_example(&example).unwrap();
+        assert_eq!(added, vec!["DB_URL", "DB_PASSWORD"]);
+        assert_eq!(
+            env.to_string(),
+            "# mine\nPORT=3000 # local\nEXTRA=1\n\n# database\n# @description connection string\nDB_URL='postgres://localhost/app'\n# @secret\nDB_PASSWORD=changeme\n"
+        );
+    }
+
+    #[test]
+    fn nothing_missing_leaves_document_unchanged() {
+        let contents = "A=1\nB=2";
+        let mut env = EnvDocument::parse(contents).unwrap();
+        let example = EnvDocument::parse("B=x\nA=y\n").unwrap();
+        assert!(env.sync_from_example(&example).unwrap().is_empty());
+        assert_eq!(env.to_string(), contents);
+    }
+}
@@ -0,0 +1,272 @@
+// reading and writing files for the `EnvironmentFile=` setting of systemd units
+use crate::shell::is_variable_name;
+use crate::{Annotations, ConvertError, EnvEntry, ParsedEnv};
+
+/// the characters a backslash escapes inside double quotes. any other escaped character keeps
+/// its backslash
+const DOUBLE_QUOTE_ESCAPES: &[char] = &['"', '\\', '`', '$'];
+
+/// the parts of an assignment the parser is in, following systemd's own parser
+enum State {
+    PreKey,
+    Key,
+    PreValue,
+    Value,
+    ValueEscape,
+    SingleQuoted,
+    DoubleQuoted,
+    DoubleQuotedEscape,
+    Comment,
+    CommentEscape,
+}
+
+/// whitespace as systemd counts it
+fn is_space(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\n' | '\r')
+}
+
+/// keeps an assignment if systemd would: the key must be a valid variable name and the value may
+/// not hold control characters other than newlines and tabs
+fn push(entries: &mut Vec<EnvEntry>, key: &str, value: String, line: u64) {
+    let key = key.trim_end();
+    let valid_value = !value
+        .chars()
+        .any(|c| c.is_control() && c != '\n' && c != '\t');
+    if is_variable_name(key) && valid_value {
+        entries.push(EnvEntry {
+            key: key.to_string(),
+            value,
+            line,
+            annotations: Annotations::default(),
+        });
+    }
+}
+
+/// Parses a file the way systemd reads an `EnvironmentFile=`:
+/// - lines starting with `#` or `;` are comments, and anywhere else both are part of the value
+/// - whitespace around keys and unquoted values is trimmed
+/// - a backslash at the end of a line continues the value on the next line, and elsewhere in an
+///   unquoted value keeps the character after it
+/// - single quotes keep everything up to the closing quote, and double quotes do the same except
+///   that `\"`, `\\`, `` \` `` and `\$` are unescaped. Other sequences such as `\n` are kept as
+///   written, since systemd only applies C escapes to `Environment=` in the unit file itself
+/// - quotes only count at the start of a value or straight after a quoted part, and parts
+///   written next to each other are joined into one value
+///
+/// Like systemd, assignments with an invalid key or a value holding control characters are
+/// skipped rather than reported, so parsing never fails.
+/// ```rust
+/// # use dotenv_lib::parse_systemd_env_file;
+/// let env = parse_systemd_env_file("; comment\nGREETING=\"hello\\nthere\" # world\nPATHS=/a:\\\n/b\n");
+/// assert_eq!(env.get_str("GREETING"), Some("hello\\nthere# world"));
+/// assert_eq!(env.get_str("PATHS"), Some("/a:/b"));
+/// ```
+pub fn parse_systemd_env_file(contents: &str) -> ParsedEnv {
+    let mut entries = Vec::new();
+    let mut state = State::PreKey;
+    let mut key = String::new();
+    let mut value = String::new();
+    // the length of `value` before its trailing whitespace, which is trimmed when it is unquoted
+    let mut value_end = 0;
+    let mut line = 1;
+    let mut key_line = 1;
+    for c in contents.chars() {
+        let newline = c == '\n' || c == '\r';
+        state = match state {
+            State::PreKey if c == '#' || c == ';' => State::Comment,
+            State::PreKey if is_space(c) => State::PreKey,
+            State::PreKey => {
+                key.clear();
+                key.push(c);
+                key_line = line;
+                State::Key
+            }
+            // a key without a value is skipped
+            State::Key if newline => State::PreKey,
+            State::Key if c == '=' => {
+                value.clear();
+                value_end = 0;
+                State::PreValue
+            }
+            State::Key => {
+                key.push(c);
+                State::Key
+            }
+            State::PreValue | State::Value if newline => {
+                value.truncate(value_end);
+                push(&mut entries, &key, std::mem::take(&mut value), key_line);
+                State::PreKey
+            }
+            State::PreValue if c == '\'' => State::SingleQuoted,
+            State::PreValue if c == '"' => State::DoubleQuoted,
+            State::PreValue | State::Value if c == '\\' => {
+                value_end = value.len();
+                State::ValueEscape
+            }
+            State::PreValue if is_space(c) => State::PreValue,
+            State::PreValue | State::Value => {
+                value.push(c);
+                if !is_space(c) {
+                    value_end = value.len();
+                }
+                State::Value
+            }
+            State::ValueEscape => {
+                // an escaped newline continues the value on the next line
+                if !newline {
+                    value.push(c);
+                    value_end = value.len();
+                }
+                State::Value
+            }
+            State::SingleQuoted if c == '\'' => {
+                value_end = value.len();
+                State::PreValue
+            }
+            State::SingleQuoted => {
+                value.push(c);
+                State::SingleQuoted
+            }
+            State::DoubleQuoted if c == '"' => {
+                value_end = value.len();
+                State::PreValue
+            }
+            State::DoubleQuoted if c == '\\' => State::DoubleQuotedEscape,
+            State::DoubleQuoted => {
+                value.push(c);
+                State::DoubleQuoted
+            }
+            State::DoubleQuotedEscape => {
+                if DOUBLE_QUOTE_ESCAPES.contains(&c) {
+                    value.push(c);
+                } else if c != '\n' {
+                    value.push('\\');
+                    value.push(c);
+                }
+                State::DoubleQuoted
+            }
+            State::Comment if c == '\\' => State::CommentEscape,
+            State::Comment if newline => State::PreKey,
+            State::Comment => State::Comment,
+            State::CommentEscape if newline => State::PreKey,
+            State::CommentEscape => State::Comment,
+        };
+        if c == '\n' {
+            line += 1;
+        }
+    }
+    match state {
+        State::PreValue | State::Value | State::ValueEscape => {
+            value.truncate(value_end);
+            push(&mut entries, &key, value, key_line);
+        }
+        // an unclosed quote runs to the end of the file
+        State::SingleQuoted | State::DoubleQuoted | State::DoubleQuotedEscape => {
+            push(&mut entries, &key, value, key_line);
+        }
+        _ => {}
+    }
+    ParsedEnv::from_entries(entries)
+}
+
+/// Writes a file for `EnvironmentFile=` that [`parse_systemd_env_file`], and systemd, read back as
+/// the given values. Values are double quoted unless they only hold characters with no special
+/// meaning, with `"`, `\`, `` ` `` and `$` escaped. Line breaks are kept inside the quotes. Keys
+/// must be valid variable names, and values may not hold control characters other than newlines
+/// and tabs, since systemd skips such assignments.
+/// ```rust
+/// # use dotenv_lib::systemd_env_file;
+/// let file = systemd_env_file([("PORT", "8080"), ("GREETING", "say \"hi\"")]).unwrap();
+/// assert_eq!(file, "PORT=8080\nGREETING=\"say \\\"hi\\\"\"\n");
+/// ```
+pub fn systemd_env_file<'a>(
+    vars: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> Result<String, ConvertError> {
+    let mut file = String::new();
+    for (key, value) in vars {
+        let unrepresentable = |reason: &str| ConvertError::Unrepresentable {
+            key: key.to_string(),
+            reason: reason.to_string(),
+        };
+        if !is_variable_name(key) {
+            return Err(unrepresentable("not a valid variable name"));
+        }
+        if value
+            .chars()
+            .any(|c| c.is_control() && c != '\n' && c != '\t')
+        {
+            return Err(unrepresentable(
+                "systemd skips values holding control characters",
+            ));
+        }
+        let plain = value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-+./:,@%=".contains(c));
+        if plain {
+            file.push_str(&format!("{key}={value}\n"));
+        } else {
+            let mut quoted = String::with_capacity(value.len() + 2);
+            for c in value.chars() {
+                if DOUBLE_QUOTE_ESCAPES.contains(&c) {
+                    quoted.push('\\');
+                }
+                quoted.push(c);
+            }
+            file.push_str(&format!("{key}=\"{quoted}\"\n"));
+        }
+    }
+    Ok(file)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_systemd_env_file, systemd_env_file};
+
+    #[test]
+    fn parse_like_systemd() {
+        let contents = "# comment \\\n  ; another\n  A = spaced value  \nB='single \\n $x'  \nC=\"a \\\"b\\\" \\$c \\t\"\nD=one\\\ntwo\nE=\"multi\nline\"\nF=\"y z\" 'w'x\nF2=x\"y\"\nG=\nH=#literal ;too\nNO_VALUE\nBAD KEY=1\nI=\"unclosed";
+        let env = parse_systemd_env_file(contents);
+        let values: Vec<(&str, &str, u64)> = env
+            .entries()
+            .iter()
+            .map(|e| (e.key.as_str(), e.value.as_str(), e.line))
+            .collect();
+        assert_eq!(
+            values,
+            [
+                ("A", "spaced value", 3),
+                ("B", "single \\n $x", 4),
+                ("C", "a \"b\" $c \\t", 5),
+                ("D", "onetwo", 6),
+                ("E", "multi\nline", 8),
+                ("F", "y zwx", 10),
+                ("F2", "x\"y\"", 11),
+                ("G", "", 12),
+                ("H", "#literal ;too", 13),
+                ("I", "unclosed", 16),
+            ]
+        );
+    }
+
+    #[test]
+    fn write_and_read_back() {
+        let vars = [
+            ("PLAIN", "/usr/bin:/bin"),
+            ("EMPTY", ""),
+            ("QUOTES", "it's \"$HOME\" `id` \\n"),
+            ("SPACES", "  padded # ; "),
+            ("MULTI", "line one\n\tline two\n"),
+        ];
+        let file = systemd_env_file(vars).unwrap();
+        assert!(file.starts_with("PLAIN=/usr/bin:/bin\nEMPTY=\n"));
+        let env = parse_systemd_env_file(&file);
+        let values: Vec<(&str, &str)> = env
+            .entries()
+            .iter()
+            .map(|e| (e.key.as_str(), e.value.as_str()))
+            .collect();
+        assert_eq!(values, vars);
+        assert!(systemd_env_file([("CR", "a\rb")]).is_err());
+        assert!(systemd_env_file([("NOT-VALID", "x")]).is_err());
+    }
+}
@@ -0,0 +1,308 @@
+// typed accessors for values in a parsed `.env` file
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::{EnvEntry, ParsedEnv};
+
+#[derive(Debug, PartialEq)]
+pub enum ValueError {
+    Missing {
+        key: String,
+    },
+    Invalid {
+        key: String,
+        value: String,
+        line: u64,
+        reason: String,
+    },
+    /// a declared default that does not hold a valid value for its key
+    InvalidDefault {
+        key: String,
+        default: String,
+        reason: String,
+    },
+}
+
+impl fmt::Display for ValueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValueError::Missing { key } => write!(f, "Key '{key}' is not set"),
+            ValueError::Invalid {
+                key,
+                value,
+                line,
+                reason,
+            } => write!(
+                f,
+                "Invalid value '{value}' for key '{key}' on line {line}: {reason}"
+            ),
+            ValueError::InvalidDefault {
+                key,
+                default,
+                reason,
+            } => write!(f, "Invalid default '{default}' for key '{key}': {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for ValueError {}
+
+impl ValueError {
+    pub(crate) fn invalid(entry: &EnvEntry, reason: impl Into<String>) -> Self {
+        ValueError::Invalid {
+            key: entry.key.clone(),
+            value: entry.value.clone(),
+            line: entry.line,
+            reason: reason.into(),
+        }
+    }
+}
+
+/// reads 1/0, true/false, yes/no and on/off in any case
+pub(crate) fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Some(true),
+        "0" | "false" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+/// reads durations such as `500ms`, `30s`, `5m`, `2h`, `1d` or `1h30m`.
+/// a bare number is read as seconds
+pub(crate) fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err("expected a duration such as 30s or 5m".to_string());
+    }
+    if let Ok(secs) = value.parse::<u64>() {
+        return Ok(Duration::from_secs(secs));
+    }
+
+    let mut total = Duration::ZERO;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let digits_end = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        if digits_end == 0 {
+            return Err(format!("expected a number before '{rest}'"));
+        }
+        let amount: u64 = rest[..digits_end]
+            .parse()
+            .map_err(|_| format!("'{}' is too large", &rest[..digits_end]))?;
+        rest = &rest[digits_end..];
+        let unit_end = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let part = match &rest[..unit_end] {
+            "ms" => Duration::from_millis(amount),
+            "s" => Duration::from_secs(amount),
+            "m" => Duration::from_secs(amount.saturating_mul(60)),
+            "h" => Duration::from_secs(amount.saturating_mul(60 * 60)),
+            "d" => Duration::from_secs(amount.saturating_mul(24 * 60 * 60)),
+            "" => return Err(format!("missing unit after '{amount}'")),
+            unit => return Err(format!("unknown duration unit '{unit}'")),
+        };
+        total = total.saturating_add(part);
+        rest = &rest[unit_end..];
+    }
+    Ok(total)
+}
+
+/// reads sizes such as `512`, `10KB` or `10MiB`. decimal units (KB, MB, ...) are powers of 1000,
+/// binary units (KiB, MiB, ...) are powers of 1024
+pub(crate) fn parse_bytes(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let digits_end = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    if digits_end == 0 {
+        return Err("expected a size such as 512, 10KB or 10MiB".to_string());
+    }
+    let amount: u64 = value[..digits_end]
+        .parse()
+        .map_err(|_| "size is too large".to_string())?;
+    let multiplier: u64 = match value[digits_end..].trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" => 1000,
+        "m" | "mb" => 1000u64.pow(2),
+        "g" | "gb" => 1000u64.pow(3),
+        "t" | "tb" => 1000u64.pow(4),
+        "kib" => 1 << 10,
+        "mib" => 1 << 20,
+        "gib" => 1 << 30,
+        "tib" => 1 << 40,
+        unit => return Err(format!("unknown size unit '{unit}'")),
+    };
+    amount
+        .checked_mul(multiplier)
+        .ok_or_else(|| "size is too large".to_string())
+}
+
+impl ParsedEnv {
+    fn require(&self, key: &str) -> Result<&EnvEntry, ValueError> {
+        self.entry(key).ok_or_else(|| ValueError::Missing {
+            key: key.to_string(),
+        })
+    }
+
+    /// Parses the value of `key` with its [`FromStr`] implementation.
+    /// ```rust
+    /// # use dotenv_lib::{parse_env, ValueError};
+    /// let env = parse_env("PORT=eighty\n".to_string()).unwrap();
+    /// let err = env.get::<u16>("PORT").unwrap_err();
+    /// assert!(matches!(err, ValueError::Invalid { line: 1, .. }));
+    /// ```
+    pub fn get<T>(&self, key: &str) -> Result<T, ValueError>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        let entry = self.require(key)?;
+        entry
+            .value
+            .parse()
+            .map_err(|err: T::Err| ValueError::invalid(entry, err.to_string()))
+    }
+
+    /// Reads the value of `key` as a boolean, accepting 1/0, true/false, yes/no and on/off in any case.
+    pub fn get_bool(&self, key: &str) -> Result<bool, ValueError> {
+        let entry = self.require(key)?;
+        parse_bool(&entry.value).ok_or_else(|| {
+            ValueError::invalid(entry, "expected one of 1/0, true/false, yes/no, on/off")
+        })
+    }
+
+    /// Splits the value of `key` on `separator` and parses each trimmed, non-empty item.
+    /// ```rust
+    /// # use dotenv_lib::parse_env;
+    /// let env = parse_env("PORTS='80, 443'\n".to_string()).unwrap();
+    /// assert_eq!(env.get_list::<u16>("PORTS", ',').unwrap(), vec![80, 443]);
+    /// ```
+    pub fn get_list<T>(&self, key: &str, separator: char) -> Result<Vec<T>, ValueError>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        let entry = self.require(key)?;
+        entry
+            .value
+            .split(separator)
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(|item| {
+                item.parse().map_err(|err: T::Err| {
+                    ValueError::invalid(entry, format!("item '{item}': {err}"))
+                })
+            })
+            .collect()
+    }
+
+    /// Reads the value of `key` as a duration such as `500ms`, `30s`, `5m`, `2h`, `1d` or `1h30m`.
+    /// A bare number is read as seconds.
+    pub fn get_duration(&self, key: &str) -> Result<Duration, ValueError> {
+        let entry = self.require(key)?;
+        parse_duration(&entry.value).map_err(|reason| ValueError::invalid(entry, reason))
+    }
+
+    /// Reads the value of `key` as a number of bytes, such as `512`, `10KB` or `10MiB`.
+    pub fn get_bytes(&self, key: &str) -> Result<u64, ValueError> {
+        let entry = self.require(key)?;
+        parse_bytes(&entry.value).map_err(|reason| ValueError::invalid(entry, reason))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{ValueError, parse_env};
+
+    #[test]
+    fn get_from_str_values() {
+        let env = parse_env("PORT=8080\nRATIO=0.5\nNAME=api\n".to_string()).unwrap();
+        assert_eq!(env.get::<u16>("PORT").unwrap(), 8080);
+        assert_eq!(env.get::<f64>("RATIO").unwrap(), 0.5);
+        assert_eq!(env.get::<String>("NAME").unwrap(), "api");
+    }
+
+    /// errors should name the key, the bad value and the line
+    #[test]
+    fn invalid_value_reports_key_value_and_line() {
+        let env = parse_env("# header\nHOST=localhost\nPORT=http\n".to_string()).unwrap();
+        let err = env.get::<u16>("PORT").unwrap_err();
+        match &err {
+            ValueError::Invalid {
+                key, value, line, ..
+            } => {
+                assert_eq!(key, "PORT");
+                assert_eq!(value, "http");
+                assert_eq!(*line, 3);
+            }
+            _ => panic!("Did not return correct error"),
+        }
+        let message = err.to_string();
+        assert!(message.contains("PORT") && message.contains("http") && message.contains("line 3"));
+    }
+
+    #[test]
+    fn missing_key_err() {
+        let env = parse_env("A=1\n".to_string()).unwrap();
+        assert_eq!(
+            env.get::<u8>("B"),
+            Err(ValueError::Missing {
+                key: "B".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn truthy_values() {
+        let env = parse_env("A=YES\nB=off\nC=1\nD=False\nE=maybe\n".to_string()).unwrap();
+        assert!(env.get_bool("A").unwrap());
+        assert!(!env.get_bool("B").unwrap());
+        assert!(env.get_bool("C").unwrap());
+        assert!(!env.get_bool("D").unwrap());
+        assert!(env.get_bool("E").is_err());
+    }
+
+    #[test]
+    fn list_values() {
+        let env = parse_env("HOSTS=a:b::c\nNUMS='1, 2, x'\n".to_string()).unwrap();
+        assert_eq!(
+            env.get_list::<String>("HOSTS", ':').unwrap(),
+            vec!["a", "b", "c"]
+        );
+        assert!(env.get_list::<u8>("NUMS", ',').is_err());
+    }
+
+    #[test]
+    fn duration_values() {
+        let env = parse_env("A=30s\nB=5m\nC=1h30m\nD=250ms\nE=10\nF=5x\n".to_string()).unwrap();
+        assert_eq!(env.get_duration("A").unwrap(), Duration::from_secs(30));
+        assert_eq!(env.get_duration("B").unwrap(), Duration::from_secs(300));
+        assert_eq!(env.get_duration("C").unwrap(), Duration::from_secs(5400));
+        assert_eq!(env.get_duration("D").unwrap(), Duration::from_millis(250));
+        assert_eq!(env.get_duration("E").unwrap(), Duration::from_secs(10));
+        assert!(env.get_duration("F").is_err());
+    }
+
+    #[test]
+    fn byte_values() {
+        let env = parse_env("A=10MiB\nB=10MB\nC=512\nD='2 kib'\nE=1XB\n".to_string()).unwrap();
+        assert_eq!(env.get_bytes("A").unwrap(), 10 * 1024 * 1024);
+        assert_eq!(env.get_bytes("B").unwrap(), 10_000_000);
+        assert_eq!(env.get_bytes("C").unwrap(), 512);
+        assert_eq!(env.get_bytes("D").unwrap(), 2048);
+        assert!(env.get_bytes("E").is_err());
+    }
+
+    /// lines after a multi-line quoted value should still be counted correctly
+    #[test]
+    fn line_after_multiline_value() {
+        let env = parse_env("A='one\ntwo'\nB=x\n".to_string()).unwrap();
+        assert_eq!(env.line("A"), Some(1));
+        assert_eq!(env.line("B"), Some(3));
+    }
+}